@@ -0,0 +1,818 @@
+// Vector annotations (arrows, shapes, freehand paths, text boxes, numbered step badges,
+// magnifier insets) drawn in the selection window and carried in SaveData. They are rendered
+// anti-aliased onto the cropped image, before the timestamp, so saved files and clipboard
+// copies contain the markup.
+//
+// Coordinates are logical pixels relative to the top-left corner of the selection, like
+// SelectionBounds; they are scaled by the scale factor of the capture when rendered.
+
+use ab_glyph::{Font, FontArc, OutlineCurve, PxScale, ScaleFont};
+use crate::AppState;
+use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+use tiny_skia::{
+    FillRule, FilterQuality, IntRect, LineCap, LineJoin, Paint, PathBuilder, Pattern, Pixmap, PremultipliedColorU8,
+    Rect, SpreadMode, Stroke, Transform,
+};
+
+// Padding around the text of a text box (logical pixels)
+const TEXT_PADDING: f32 = 4.0;
+// Number of the first step badge, after a reset
+pub const FIRST_STEP: u32 = 1;
+// Magnification range of the magnifier insets
+const MIN_ZOOM: f32 = 1.0;
+const MAX_ZOOM: f32 = 8.0;
+
+fn default_stroke_width() -> f32 {
+    3.0
+}
+
+fn default_font_size() -> f32 {
+    20.0
+}
+
+fn default_badge_size() -> f32 {
+    32.0
+}
+
+fn default_badge_text_color() -> String {
+    "#ffffff".to_string()
+}
+
+fn default_zoom() -> f32 {
+    2.0
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default)]
+pub enum BadgeShape {
+    #[serde(rename = "circle")]
+    #[default]
+    Circle,
+    #[serde(rename = "square")]
+    Square, // Rounded corners
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct AnnotationStyle {
+    pub color: String, // "#rrggbb" or "#rrggbbaa"
+    #[serde(rename = "strokeWidth", default = "default_stroke_width")]
+    pub stroke_width: f32,
+    #[serde(default)]
+    pub fill: Option<String>, // Shapes and text box background, None = not filled
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(tag = "kind")]
+pub enum Annotation {
+    #[serde(rename = "arrow")]
+    Arrow {
+        from: [f32; 2],
+        to: [f32; 2],
+        #[serde(flatten)]
+        style: AnnotationStyle,
+    },
+    #[serde(rename = "line")]
+    Line {
+        from: [f32; 2],
+        to: [f32; 2],
+        #[serde(flatten)]
+        style: AnnotationStyle,
+    },
+    #[serde(rename = "rectangle")]
+    Rectangle {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        #[serde(flatten)]
+        style: AnnotationStyle,
+    },
+    #[serde(rename = "ellipse")]
+    Ellipse {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        #[serde(flatten)]
+        style: AnnotationStyle,
+    },
+    #[serde(rename = "path")]
+    Path {
+        points: Vec<[f32; 2]>,
+        #[serde(flatten)]
+        style: AnnotationStyle,
+    },
+    #[serde(rename = "text")]
+    Text {
+        x: f32,
+        y: f32,
+        #[serde(default)]
+        width: Option<f32>, // Wrapping width, None = one line per paragraph
+        text: String,
+        #[serde(rename = "fontSize", default = "default_font_size")]
+        font_size: f32,
+        #[serde(flatten)]
+        style: AnnotationStyle,
+    },
+    // Numbered step callout: `color` fills the badge, the number and the ring (stroke
+    // width) use `textColor`
+    #[serde(rename = "badge")]
+    Badge {
+        x: f32, // Center
+        y: f32,
+        number: u32,
+        #[serde(default)]
+        shape: BadgeShape,
+        #[serde(default = "default_badge_size")]
+        size: f32, // Diameter or side
+        #[serde(rename = "textColor", default = "default_badge_text_color")]
+        text_color: String,
+        #[serde(flatten)]
+        style: AnnotationStyle,
+    },
+    // Zoomed copy of the `x`/`y`/`width`/`height` region with its top-left corner at `target`,
+    // framed and joined to the region by connector lines (all in `color`)
+    #[serde(rename = "magnifier")]
+    Magnifier {
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        target: [f32; 2],
+        #[serde(default = "default_zoom")]
+        zoom: f32,
+        #[serde(default)]
+        smooth: bool, // Bicubic filtering, nearest-neighbour otherwise (sharp pixels)
+        #[serde(flatten)]
+        style: AnnotationStyle,
+    },
+}
+
+// "#rrggbb" or "#rrggbbaa" as RGBA (opaque without alpha), shared with the redactions
+pub fn parse_color(value: &str) -> Result<[u8; 4], String> {
+    let hex = value.trim().trim_start_matches('#');
+    let channel = |i: usize| {
+        hex.get(i..i + 2)
+            .and_then(|c| u8::from_str_radix(c, 16).ok())
+            .ok_or_else(|| format!("Invalid color: {}", value))
+    };
+    match hex.len() {
+        6 => Ok([channel(0)?, channel(2)?, channel(4)?, 255]),
+        8 => Ok([channel(0)?, channel(2)?, channel(4)?, channel(6)?]),
+        _ => Err(format!("Invalid color: {}", value)),
+    }
+}
+
+fn paint(color: &str) -> Result<Paint<'static>, String> {
+    let [r, g, b, a] = parse_color(color)?;
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(r, g, b, a);
+    paint.anti_alias = true;
+    Ok(paint)
+}
+
+fn stroke(width: f32) -> Stroke {
+    Stroke { width: width.max(0.5), line_cap: LineCap::Round, line_join: LineJoin::Round, ..Default::default() }
+}
+
+// Fill (when asked) then outline a closed shape
+fn draw_shape(pixmap: &mut Pixmap, path: &tiny_skia::Path, style: &AnnotationStyle, transform: Transform) -> Result<(), String> {
+    if let Some(fill) = &style.fill {
+        pixmap.fill_path(path, &paint(fill)?, FillRule::Winding, transform, None);
+    }
+    if style.stroke_width > 0.0 {
+        pixmap.stroke_path(path, &paint(&style.color)?, &stroke(style.stroke_width), transform, None);
+    }
+    Ok(())
+}
+
+fn draw_arrow(pixmap: &mut Pixmap, from: [f32; 2], to: [f32; 2], style: &AnnotationStyle, transform: Transform) -> Result<(), String> {
+    let (dx, dy) = (to[0] - from[0], to[1] - from[1]);
+    let length = (dx * dx + dy * dy).sqrt();
+    if length < 1.0 {
+        return Ok(());
+    }
+    let (ux, uy) = (dx / length, dy / length);
+    let head_length = (style.stroke_width * 4.0).max(12.0).min(length);
+    let head_half_width = head_length * 0.5;
+    let base = [to[0] - ux * head_length, to[1] - uy * head_length];
+
+    // Shaft stops under the head so its round cap does not stick out of the tip
+    let mut shaft = PathBuilder::new();
+    shaft.move_to(from[0], from[1]);
+    shaft.line_to(base[0] + ux, base[1] + uy);
+    if let Some(path) = shaft.finish() {
+        pixmap.stroke_path(&path, &paint(&style.color)?, &stroke(style.stroke_width), transform, None);
+    }
+
+    let mut head = PathBuilder::new();
+    head.move_to(to[0], to[1]);
+    head.line_to(base[0] - uy * head_half_width, base[1] + ux * head_half_width);
+    head.line_to(base[0] + uy * head_half_width, base[1] - ux * head_half_width);
+    head.close();
+    if let Some(path) = head.finish() {
+        pixmap.fill_path(&path, &paint(&style.color)?, FillRule::Winding, transform, None);
+    }
+    Ok(())
+}
+
+// Split a paragraph into lines no wider than `max_width` (a long word keeps its own line)
+fn wrap_lines(font: &ab_glyph::PxScaleFont<&FontArc>, text: &str, max_width: Option<f32>) -> Vec<String> {
+    let measure = |line: &str| line.chars().map(|c| font.h_advance(font.glyph_id(c))).sum::<f32>();
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let Some(max_width) = max_width else {
+            lines.push(paragraph.to_string());
+            continue;
+        };
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let candidate = if line.is_empty() { word.to_string() } else { format!("{} {}", line, word) };
+            if measure(&candidate) > max_width && !line.is_empty() {
+                lines.push(std::mem::replace(&mut line, word.to_string()));
+            } else {
+                line = candidate;
+            }
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+// Glyph outlines of one line as a path, baseline at `y`
+fn text_path(font: &FontArc, scale: PxScale, line: &str, x: f32, y: f32) -> Option<tiny_skia::Path> {
+    let scaled = font.as_scaled(scale);
+    let (sx, sy) = (scaled.h_scale_factor(), scaled.v_scale_factor());
+    let mut builder = PathBuilder::new();
+    let mut caret = x;
+    let mut previous = None;
+
+    for c in line.chars() {
+        let glyph_id = font.glyph_id(c);
+        if let Some(previous) = previous {
+            caret += scaled.kern(previous, glyph_id);
+        }
+        previous = Some(glyph_id);
+
+        if let Some(outline) = font.outline(glyph_id) {
+            // Font units, y up; each contour is a chain of curves
+            let point = |p: ab_glyph::Point| (caret + p.x * sx, y - p.y * sy);
+            let mut last_end: Option<(f32, f32)> = None;
+            for curve in &outline.curves {
+                let (start, end) = match curve {
+                    OutlineCurve::Line(a, b) | OutlineCurve::Quad(a, _, b) | OutlineCurve::Cubic(a, _, _, b) => {
+                        (point(*a), point(*b))
+                    }
+                };
+                if last_end != Some(start) {
+                    if last_end.is_some() {
+                        builder.close();
+                    }
+                    builder.move_to(start.0, start.1);
+                }
+                match curve {
+                    OutlineCurve::Line(_, _) => builder.line_to(end.0, end.1),
+                    OutlineCurve::Quad(_, control, _) => {
+                        let control = point(*control);
+                        builder.quad_to(control.0, control.1, end.0, end.1);
+                    }
+                    OutlineCurve::Cubic(_, c1, c2, _) => {
+                        let (c1, c2) = (point(*c1), point(*c2));
+                        builder.cubic_to(c1.0, c1.1, c2.0, c2.1, end.0, end.1);
+                    }
+                }
+                last_end = Some(end);
+            }
+            if last_end.is_some() {
+                builder.close();
+            }
+        }
+        caret += scaled.h_advance(glyph_id);
+    }
+    builder.finish()
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_text(
+    pixmap: &mut Pixmap,
+    font: &FontArc,
+    x: f32,
+    y: f32,
+    width: Option<f32>,
+    text: &str,
+    font_size: f32,
+    style: &AnnotationStyle,
+    transform: Transform,
+) -> Result<(), String> {
+    let scale = PxScale::from(font_size.max(4.0));
+    let scaled = font.as_scaled(scale);
+    let text_width = width.map(|w| (w - 2.0 * TEXT_PADDING).max(1.0));
+    let lines = wrap_lines(&scaled, text, text_width);
+    let line_height = scaled.height() + scaled.line_gap();
+
+    // Background box
+    if let Some(fill) = &style.fill {
+        let widest = lines
+            .iter()
+            .map(|line| line.chars().map(|c| scaled.h_advance(font.glyph_id(c))).sum::<f32>())
+            .fold(0.0, f32::max);
+        let box_width = width.unwrap_or(widest + 2.0 * TEXT_PADDING);
+        let box_height = line_height * lines.len() as f32 + 2.0 * TEXT_PADDING;
+        if let Some(rect) = Rect::from_xywh(x, y, box_width, box_height) {
+            pixmap.fill_rect(rect, &paint(fill)?, transform, None);
+        }
+    }
+
+    let text_paint = paint(&style.color)?;
+    for (index, line) in lines.iter().enumerate() {
+        let baseline = y + TEXT_PADDING + scaled.ascent() + line_height * index as f32;
+        if let Some(path) = text_path(font, scale, line, x + TEXT_PADDING, baseline) {
+            pixmap.fill_path(&path, &text_paint, FillRule::Winding, transform, None);
+        }
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_badge(
+    pixmap: &mut Pixmap,
+    font: &FontArc,
+    center: [f32; 2],
+    number: u32,
+    shape: BadgeShape,
+    size: f32,
+    text_color: &str,
+    style: &AnnotationStyle,
+    transform: Transform,
+) -> Result<(), String> {
+    let size = size.max(8.0);
+    let half = size / 2.0;
+    let outline = match shape {
+        BadgeShape::Circle => PathBuilder::from_circle(center[0], center[1], half),
+        BadgeShape::Square => {
+            let (left, top, right, bottom) = (center[0] - half, center[1] - half, center[0] + half, center[1] + half);
+            let radius = size * 0.2;
+            let mut builder = PathBuilder::new();
+            builder.move_to(left + radius, top);
+            builder.line_to(right - radius, top);
+            builder.quad_to(right, top, right, top + radius);
+            builder.line_to(right, bottom - radius);
+            builder.quad_to(right, bottom, right - radius, bottom);
+            builder.line_to(left + radius, bottom);
+            builder.quad_to(left, bottom, left, bottom - radius);
+            builder.line_to(left, top + radius);
+            builder.quad_to(left, top, left + radius, top);
+            builder.close();
+            builder.finish()
+        }
+    };
+    let Some(outline) = outline else {
+        return Ok(());
+    };
+    pixmap.fill_path(&outline, &paint(&style.color)?, FillRule::Winding, transform, None);
+    if style.stroke_width > 0.0 {
+        pixmap.stroke_path(&outline, &paint(text_color)?, &stroke(style.stroke_width / 2.0), transform, None);
+    }
+
+    // Number centered on its ink bounds, smaller as it gets longer
+    let label = number.to_string();
+    let font_size = size * match label.len() {
+        1 => 0.6,
+        2 => 0.5,
+        _ => 0.38,
+    };
+    let number_path = text_path(font, PxScale::from(font_size), &label, 0.0, 0.0).and_then(|path| {
+        let bounds = path.bounds();
+        let (dx, dy) = (center[0] - bounds.x() - bounds.width() / 2.0, center[1] - bounds.y() - bounds.height() / 2.0);
+        path.transform(Transform::from_translate(dx, dy))
+    });
+    if let Some(path) = number_path {
+        pixmap.fill_path(&path, &paint(text_color)?, FillRule::Winding, transform, None);
+    }
+    Ok(())
+}
+
+// Corner pairs of the source and inset rectangles whose lines bound both of them; insets are
+// scaled copies of their source, so these are the hull edges joining the two rectangles
+fn connectors(source: Rect, inset: Rect) -> Vec<([f32; 2], [f32; 2])> {
+    let corners = |r: Rect| [[r.left(), r.top()], [r.right(), r.top()], [r.right(), r.bottom()], [r.left(), r.bottom()]];
+    let (from, to) = (corners(source), corners(inset));
+    let all: Vec<[f32; 2]> = from.iter().chain(to.iter()).copied().collect();
+
+    let mut lines = Vec::new();
+    for (a, b) in from.iter().zip(to.iter()) {
+        let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+        if dx.abs() < 0.5 && dy.abs() < 0.5 {
+            continue;
+        }
+        let side = |c: &[f32; 2]| dx * (c[1] - a[1]) - dy * (c[0] - a[0]);
+        let tolerance = 1e-3 * (dx * dx + dy * dy).sqrt();
+        let sides: Vec<f32> = all.iter().map(side).collect();
+        let bounding = sides.iter().all(|&s| s >= -tolerance) || sides.iter().all(|&s| s <= tolerance);
+        // Aligned edges put two corner pairs on the same line: keep one of them
+        let already_drawn = lines.iter().any(|(from, to)| side(from).abs() <= tolerance && side(to).abs() <= tolerance);
+        if bounding && !already_drawn {
+            lines.push((*a, *b));
+        }
+    }
+    lines.truncate(2);
+    lines
+}
+
+#[allow(clippy::too_many_arguments)]
+fn draw_magnifier(
+    pixmap: &mut Pixmap,
+    source: [f32; 4],
+    target: [f32; 2],
+    zoom: f32,
+    smooth: bool,
+    style: &AnnotationStyle,
+    transform: Transform,
+) -> Result<(), String> {
+    let [x, y, width, height] = source;
+    let zoom = zoom.clamp(MIN_ZOOM, MAX_ZOOM);
+    let (Some(source_rect), Some(inset_rect)) = (
+        Rect::from_xywh(x, y, width, height),
+        Rect::from_xywh(target[0], target[1], width * zoom, height * zoom),
+    ) else {
+        return Ok(());
+    };
+
+    // Pixels of the region as drawn so far (redactions and earlier annotations included)
+    let (sx, sy) = (transform.sx, transform.sy);
+    let left = (x * sx).floor().max(0.0) as i32;
+    let top = (y * sy).floor().max(0.0) as i32;
+    let right = ((x + width) * sx).ceil() as i32;
+    let bottom = ((y + height) * sy).ceil() as i32;
+    let Some(region) = IntRect::from_ltrb(left, top, right, bottom).and_then(|rect| pixmap.clone_rect(rect)) else {
+        return Ok(());
+    };
+
+    // Connectors under the inset, so they stop at its border
+    let line_width = (style.stroke_width / 2.0).max(1.0);
+    if !source_rect.intersect(&inset_rect).is_some_and(|r| r.width() > 0.0 && r.height() > 0.0) {
+        for (from, to) in connectors(source_rect, inset_rect) {
+            let mut builder = PathBuilder::new();
+            builder.move_to(from[0], from[1]);
+            builder.line_to(to[0], to[1]);
+            if let Some(path) = builder.finish() {
+                pixmap.stroke_path(&path, &paint(&style.color)?, &stroke(line_width), transform, None);
+            }
+        }
+    }
+
+    // Region pixel (u, v) is at logical ((left + u) / sx, (top + v) / sy), drawn at
+    // target + (logical - source) * zoom
+    let pattern_transform = Transform::from_translate(
+        target[0] - x * zoom + left as f32 * zoom / sx,
+        target[1] - y * zoom + top as f32 * zoom / sy,
+    )
+    .pre_scale(zoom / sx, zoom / sy);
+    let quality = if smooth { FilterQuality::Bicubic } else { FilterQuality::Nearest };
+    let inset_paint = Paint {
+        shader: Pattern::new(region.as_ref(), SpreadMode::Pad, quality, 1.0, pattern_transform),
+        ..Default::default()
+    };
+    pixmap.fill_rect(inset_rect, &inset_paint, transform, None);
+
+    let mut frame = |rect: Rect, width: f32| -> Result<(), String> {
+        let outline = Stroke { line_join: LineJoin::Miter, ..stroke(width) };
+        pixmap.stroke_path(&PathBuilder::from_rect(rect), &paint(&style.color)?, &outline, transform, None);
+        Ok(())
+    };
+    frame(source_rect, line_width)?;
+    frame(inset_rect, style.stroke_width)
+}
+
+fn draw(pixmap: &mut Pixmap, font: &FontArc, annotation: &Annotation, transform: Transform) -> Result<(), String> {
+    match annotation {
+        Annotation::Arrow { from, to, style } => draw_arrow(pixmap, *from, *to, style, transform),
+        Annotation::Line { from, to, style } => {
+            let mut builder = PathBuilder::new();
+            builder.move_to(from[0], from[1]);
+            builder.line_to(to[0], to[1]);
+            if let Some(path) = builder.finish() {
+                pixmap.stroke_path(&path, &paint(&style.color)?, &stroke(style.stroke_width), transform, None);
+            }
+            Ok(())
+        }
+        Annotation::Rectangle { x, y, width, height, style } => match Rect::from_xywh(*x, *y, *width, *height) {
+            Some(rect) => draw_shape(pixmap, &PathBuilder::from_rect(rect), style, transform),
+            None => Ok(()),
+        },
+        Annotation::Ellipse { x, y, width, height, style } => {
+            match Rect::from_xywh(*x, *y, *width, *height).and_then(PathBuilder::from_oval) {
+                Some(path) => draw_shape(pixmap, &path, style, transform),
+                None => Ok(()),
+            }
+        }
+        Annotation::Path { points, style } => {
+            let mut builder = PathBuilder::new();
+            for (index, point) in points.iter().enumerate() {
+                if index == 0 {
+                    builder.move_to(point[0], point[1]);
+                } else {
+                    builder.line_to(point[0], point[1]);
+                }
+            }
+            if let Some(path) = builder.finish() {
+                pixmap.stroke_path(&path, &paint(&style.color)?, &stroke(style.stroke_width), transform, None);
+            }
+            Ok(())
+        }
+        Annotation::Text { x, y, width, text, font_size, style } => {
+            draw_text(pixmap, font, *x, *y, *width, text, *font_size, style, transform)
+        }
+        Annotation::Badge { x, y, number, shape, size, text_color, style } => {
+            draw_badge(pixmap, font, [*x, *y], *number, *shape, *size, text_color, style, transform)
+        }
+        Annotation::Magnifier { x, y, width, height, target, zoom, smooth, style } => {
+            draw_magnifier(pixmap, [*x, *y, *width, *height], *target, *zoom, *smooth, style, transform)
+        }
+    }
+}
+
+// Render the annotations onto the image; `scale_factor` maps their logical pixels to the
+// physical pixels of the image
+pub fn render(img: &mut RgbaImage, annotations: &[Annotation], scale_factor: f32) -> Result<(), String> {
+    if annotations.is_empty() {
+        return Ok(());
+    }
+    let (width, height) = img.dimensions();
+    let mut pixmap = Pixmap::new(width, height).ok_or("Image too large for annotations")?;
+
+    // tiny-skia works on premultiplied pixels
+    for (pixel, rgba) in pixmap.pixels_mut().iter_mut().zip(img.pixels()) {
+        let [r, g, b, a] = rgba.0;
+        let premultiply = |c: u8| ((c as u16 * a as u16 + 127) / 255) as u8;
+        *pixel = PremultipliedColorU8::from_rgba(premultiply(r), premultiply(g), premultiply(b), a).unwrap();
+    }
+
+    let font = crate::load_font()?;
+    let scale = if scale_factor > 0.0 { scale_factor } else { 1.0 };
+    let transform = Transform::from_scale(scale, scale);
+    for annotation in annotations {
+        draw(&mut pixmap, &font, annotation, transform)?;
+    }
+
+    for (rgba, pixel) in img.pixels_mut().zip(pixmap.pixels()) {
+        let color = pixel.demultiply();
+        rgba.0 = [color.red(), color.green(), color.blue(), color.alpha()];
+    }
+    Ok(())
+}
+
+// Next step number after a capture: the one after its highest badge, never going back
+fn next_step_after(next: u32, annotations: &[Annotation]) -> u32 {
+    let highest = annotations
+        .iter()
+        .filter_map(|annotation| match annotation {
+            Annotation::Badge { number, .. } => Some(*number),
+            _ => None,
+        })
+        .max();
+    match highest {
+        Some(highest) => next.max(highest.saturating_add(1)),
+        None => next,
+    }
+}
+
+// Step numbering continues across the captures of a session: the next number is the one
+// after the highest badge of the last saved or copied capture
+pub fn advance_steps(state: &AppState, annotations: &[Annotation]) {
+    let mut next = state.next_step.lock().unwrap();
+    *next = next_step_after(*next, annotations);
+}
+
+#[tauri::command]
+pub async fn get_next_step(state: State<'_, AppState>) -> Result<u32, String> {
+    Ok(*state.next_step.lock().unwrap())
+}
+
+#[tauri::command]
+pub async fn reset_step_counter(state: State<'_, AppState>) -> Result<u32, String> {
+    *state.next_step.lock().unwrap() = FIRST_STEP;
+    Ok(FIRST_STEP)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    const WHITE: Rgba<u8> = Rgba([255, 255, 255, 255]);
+    const RED: Rgba<u8> = Rgba([255, 0, 0, 255]);
+    const BLUE: Rgba<u8> = Rgba([0, 0, 255, 255]);
+
+    fn style(color: &str, stroke_width: f32, fill: Option<&str>) -> AnnotationStyle {
+        AnnotationStyle { color: color.to_string(), stroke_width, fill: fill.map(str::to_string) }
+    }
+
+    // White 60x40 logical canvas at `scale`
+    fn rendered(annotations: &[Annotation], scale: f32) -> RgbaImage {
+        let side = |logical: f32| (logical * scale) as u32;
+        let mut img = RgbaImage::from_pixel(side(60.0), side(40.0), WHITE);
+        render(&mut img, annotations, scale).unwrap();
+        img
+    }
+
+    fn badge(number: u32) -> Annotation {
+        Annotation::Badge {
+            x: 10.0,
+            y: 10.0,
+            number,
+            shape: BadgeShape::Circle,
+            size: 20.0,
+            text_color: "#ffffff".to_string(),
+            style: style("#ff0000", 2.0, None),
+        }
+    }
+
+    #[test]
+    fn steps_continue_after_the_highest_badge() {
+        assert_eq!(next_step_after(FIRST_STEP, &[badge(1), badge(2), badge(3)]), 4);
+        // Gaps and order do not matter, only the highest number
+        assert_eq!(next_step_after(2, &[badge(7), badge(1)]), 8);
+        // Captures without badges keep the counter
+        let line = Annotation::Line { from: [0.0, 0.0], to: [5.0, 5.0], style: style("#ff0000", 1.0, None) };
+        assert_eq!(next_step_after(5, &[line]), 5);
+        assert_eq!(next_step_after(5, &[]), 5);
+    }
+
+    #[test]
+    fn steps_never_go_back() {
+        // Renumbered badges lower than the counter leave it where it is
+        assert_eq!(next_step_after(10, &[badge(3)]), 10);
+        // After a reset, numbering follows the badges again
+        assert_eq!(next_step_after(FIRST_STEP, &[badge(3)]), 4);
+    }
+
+    #[test]
+    fn step_counter_saturates() {
+        assert_eq!(next_step_after(FIRST_STEP, &[badge(u32::MAX)]), u32::MAX);
+        assert_eq!(next_step_after(u32::MAX, &[badge(2)]), u32::MAX);
+    }
+
+    #[test]
+    fn advance_and_reset_update_the_session_counter() {
+        let state = AppState::default();
+        advance_steps(&state, &[badge(4)]);
+        assert_eq!(*state.next_step.lock().unwrap(), 5);
+        *state.next_step.lock().unwrap() = FIRST_STEP;
+        advance_steps(&state, &[badge(1)]);
+        assert_eq!(*state.next_step.lock().unwrap(), 2);
+    }
+
+    // High-entropy image (xorshift noise), so a misplaced sample cannot match by accident
+    fn noise(width: u32, height: u32) -> RgbaImage {
+        let mut state = 7u32;
+        RgbaImage::from_fn(width, height, |_, _| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            Rgba([state as u8, (state >> 8) as u8, (state >> 16) as u8, 255])
+        })
+    }
+
+    fn magnifier(source: [f32; 4], target: [f32; 2], zoom: f32) -> Annotation {
+        let [x, y, width, height] = source;
+        Annotation::Magnifier { x, y, width, height, target, zoom, smooth: false, style: style("#ff0000", 1.0, None) }
+    }
+
+    #[test]
+    fn magnifier_inset_shows_the_source_magnified() {
+        // Source logical (5, 5) 4x4 at scale 2 = physical (10, 10) 8x8, each pixel drawn as a
+        // 3x3 block of the inset at logical (25, 5) = physical (50, 10)
+        let original = noise(120, 80);
+        let mut img = original.clone();
+        render(&mut img, &[magnifier([5.0, 5.0, 4.0, 4.0], [25.0, 5.0], 3.0)], 2.0).unwrap();
+
+        for v in 1..7 {
+            for u in 1..7 {
+                let expected = original.get_pixel(10 + u, 10 + v);
+                for (dx, dy) in [(0, 0), (1, 1), (2, 2)] {
+                    assert_eq!(img.get_pixel(50 + 3 * u + dx, 10 + 3 * v + dy), expected, "source pixel {},{}", u, v);
+                }
+            }
+        }
+        // Inset frame, and the source itself left as it was inside its frame
+        assert_eq!(*img.get_pixel(50, 30), RED);
+        assert_eq!(img.get_pixel(14, 14), original.get_pixel(14, 14));
+    }
+
+    #[test]
+    fn magnifier_source_clipped_at_the_image_border() {
+        // Source logical (-2, -2) 6x6: image pixel (0, 0) is 2 logical pixels into the source,
+        // so 4 pixels into the inset at zoom 2
+        let original = noise(60, 40);
+        let mut img = original.clone();
+        render(&mut img, &[magnifier([-2.0, -2.0, 6.0, 6.0], [20.0, 20.0], 2.0)], 1.0).unwrap();
+        for (u, v) in [(0, 0), (1, 0), (0, 1), (3, 3)] {
+            assert_eq!(img.get_pixel(24 + 2 * u, 24 + 2 * v), original.get_pixel(u, v), "source pixel {},{}", u, v);
+        }
+
+        // Source past the right and bottom edges: image pixel (56, 36) is its top-left corner
+        let mut img = original.clone();
+        render(&mut img, &[magnifier([56.0, 36.0, 8.0, 8.0], [4.0, 4.0], 2.0)], 1.0).unwrap();
+        for (u, v) in [(1, 1), (2, 1), (3, 3)] {
+            assert_eq!(img.get_pixel(4 + 2 * u, 4 + 2 * v), original.get_pixel(56 + u, 36 + v), "source pixel {},{}", u, v);
+        }
+    }
+
+    #[test]
+    fn connectors_join_the_outer_corners() {
+        let rect = |x: f32, y: f32, width: f32, height: f32| Rect::from_xywh(x, y, width, height).unwrap();
+        // Inset below and to the right: top-right and bottom-left corners bound both rectangles
+        let lines = connectors(rect(10.0, 10.0, 10.0, 10.0), rect(40.0, 40.0, 30.0, 30.0));
+        assert_eq!(lines, vec![([20.0, 10.0], [70.0, 40.0]), ([10.0, 20.0], [40.0, 70.0])]);
+
+        // Top edges aligned: one connector along them, the other at the bottom
+        let lines = connectors(rect(0.0, 0.0, 10.0, 10.0), rect(20.0, 0.0, 30.0, 30.0));
+        assert_eq!(lines, vec![([0.0, 0.0], [20.0, 0.0]), ([0.0, 10.0], [20.0, 30.0])]);
+
+        // Overlapping rectangles with the same top-left corner: no degenerate connector
+        let lines = connectors(rect(0.0, 0.0, 10.0, 10.0), rect(0.0, 0.0, 20.0, 20.0));
+        assert!(lines.iter().all(|(a, b)| a != b));
+    }
+
+    #[test]
+    fn colors_are_parsed_with_optional_alpha() {
+        assert_eq!(parse_color("#ff8000"), Ok([255, 128, 0, 255]));
+        assert_eq!(parse_color("ff800080"), Ok([255, 128, 0, 128]));
+        assert!(parse_color("#ff80").is_err());
+        assert!(parse_color("#gg8000").is_err());
+    }
+
+    #[test]
+    fn rectangle_outline_is_scaled_and_not_filled() {
+        let rectangle = Annotation::Rectangle { x: 10.0, y: 10.0, width: 20.0, height: 10.0, style: style("#ff0000", 2.0, None) };
+        let img = rendered(&[rectangle], 2.0);
+
+        // Left edge at logical x = 10, physical 20, stroke 4 px wide centered on it
+        for x in 19..21 {
+            assert_eq!(*img.get_pixel(x, 30), RED, "stroke at {}", x);
+        }
+        assert_eq!(*img.get_pixel(60, 30), RED); // Right edge
+        assert_eq!(*img.get_pixel(40, 20), RED); // Top edge
+        // Inside and outside untouched, including where the edge would be without scaling
+        assert_eq!(*img.get_pixel(40, 30), WHITE);
+        assert_eq!(*img.get_pixel(11, 30), WHITE);
+        assert_eq!(*img.get_pixel(70, 30), WHITE);
+    }
+
+    #[test]
+    fn fill_covers_the_inside_only() {
+        let rectangle = Annotation::Rectangle { x: 10.0, y: 10.0, width: 20.0, height: 10.0, style: style("#ff0000", 0.0, Some("#0000ff")) };
+        let img = rendered(&[rectangle], 2.0);
+        assert_eq!(*img.get_pixel(20, 20), BLUE);
+        assert_eq!(*img.get_pixel(59, 39), BLUE);
+        assert_eq!(*img.get_pixel(19, 30), WHITE);
+        assert_eq!(*img.get_pixel(60, 30), WHITE);
+        assert!(img.pixels().all(|p| *p != RED), "no outline with a zero stroke width");
+    }
+
+    #[test]
+    fn edges_are_anti_aliased() {
+        let line = Annotation::Line { from: [5.0, 5.0], to: [55.0, 25.0], style: style("#ff0000", 3.0, None) };
+        let img = rendered(&[line], 1.0);
+        assert_eq!(*img.get_pixel(30, 15), RED);
+        // Blend of red and white along the slanted edges
+        assert!(img.pixels().any(|p| p[0] == 255 && p[1] > 0 && p[1] < 255));
+        assert_eq!(*img.get_pixel(30, 30), WHITE);
+    }
+
+    #[test]
+    fn arrow_head_reaches_the_tip() {
+        let arrow = Annotation::Arrow { from: [5.0, 20.0], to: [55.0, 20.0], style: style("#ff0000", 2.0, None) };
+        let img = rendered(&[arrow], 1.0);
+        assert_eq!(*img.get_pixel(10, 20), RED); // Shaft
+        assert_eq!(*img.get_pixel(50, 20), RED); // Head
+        assert_eq!(*img.get_pixel(45, 23), RED); // Head wider than the shaft
+        assert_eq!(*img.get_pixel(20, 24), WHITE);
+        assert_eq!(*img.get_pixel(57, 20), WHITE);
+    }
+
+    #[test]
+    fn text_is_drawn_inside_its_box() {
+        let text = Annotation::Text {
+            x: 10.0,
+            y: 5.0,
+            width: None,
+            text: "Hi".to_string(),
+            font_size: 20.0,
+            style: style("#ff0000", 0.0, None),
+        };
+        let img = rendered(&[text], 2.0);
+        let inked: Vec<(u32, u32)> = img
+            .enumerate_pixels()
+            .filter(|(_, _, p)| **p != WHITE)
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        assert!(!inked.is_empty());
+        // Padding and font size are scaled too: glyphs start after the padding, below the
+        // top of the box (physical y = 10) and within two lines of 20 logical pixels
+        let glyph_left = ((10.0 + TEXT_PADDING) * 2.0) as u32;
+        assert!(inked.iter().all(|&(x, y)| x >= glyph_left - 1 && (10..90).contains(&y)));
+    }
+}
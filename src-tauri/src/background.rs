@@ -0,0 +1,109 @@
+// Background capture sessions (time-lapse, region watch): at most one session of each kind,
+// kept in an AppState slot while its thread runs. The thread shares the stop flag and the
+// capture counter with the slot, so the UI can stop it and read its progress.
+
+use crate::read_save_path;
+use chrono::Local;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+// Running session, kept in AppState
+pub struct Session<C> {
+    handle: SessionHandle,
+    output_folder: PathBuf,
+    config: C,
+}
+
+pub type SessionSlot<C> = Mutex<Option<Session<C>>>;
+
+// Stop flag and capture counter of a session, shared with its thread
+#[derive(Clone)]
+pub struct SessionHandle {
+    stop: Arc<AtomicBool>,
+    count: Arc<AtomicU32>,
+}
+
+impl SessionHandle {
+    pub fn stop_requested(&self) -> bool {
+        self.stop.load(Ordering::SeqCst)
+    }
+
+    // Count one more capture, returns the new total
+    pub fn add_capture(&self) -> u32 {
+        self.count.fetch_add(1, Ordering::SeqCst) + 1
+    }
+
+    pub fn captures(&self) -> u32 {
+        self.count.load(Ordering::SeqCst)
+    }
+}
+
+// Snapshot of a running session
+pub struct SessionInfo<C> {
+    pub captures: u32,
+    pub output_folder: String,
+    pub config: C,
+}
+
+// Last started configuration, used by the tray entries
+pub fn read_stored_config<C: DeserializeOwned>(app: &AppHandle, key: &str) -> Option<C> {
+    let store = app.store("settings.json").ok()?;
+    store.get(key).and_then(|v| serde_json::from_value(v).ok())
+}
+
+pub fn store_config<C: Serialize>(app: &AppHandle, key: &str, config: &C) -> Result<(), String> {
+    let store = app.store("settings.json").map_err(|e| e.to_string())?;
+    store.set(key, serde_json::to_value(config).map_err(|e| e.to_string())?);
+    store.save().map_err(|e| e.to_string())
+}
+
+// Configured folder, or a new dated folder ("<prefix>_<date>") in the save path; created
+pub fn output_folder(app: &AppHandle, configured: &Option<String>, prefix: &str) -> Result<PathBuf, String> {
+    let folder = match configured {
+        Some(folder) if !folder.is_empty() => PathBuf::from(folder),
+        _ => PathBuf::from(read_save_path(app)?)
+            .join(format!("{}_{}", prefix, Local::now().format("%Y-%m-%dT%H-%M-%S"))),
+    };
+    fs::create_dir_all(&folder).map_err(|e| e.to_string())?;
+    Ok(folder)
+}
+
+// Take the slot for a new session; fails with `busy` if one is already running
+pub fn claim<C>(slot: &SessionSlot<C>, output_folder: PathBuf, config: C, busy: &str) -> Result<SessionHandle, String> {
+    let mut session = slot.lock().unwrap();
+    if session.is_some() {
+        return Err(busy.to_string());
+    }
+    let handle = SessionHandle { stop: Arc::new(AtomicBool::new(false)), count: Arc::new(AtomicU32::new(0)) };
+    *session = Some(Session { handle: handle.clone(), output_folder, config });
+    Ok(handle)
+}
+
+// Release the session slot (only if it is still ours)
+pub fn release<C>(slot: &SessionSlot<C>, handle: &SessionHandle) {
+    let mut session = slot.lock().unwrap();
+    if session.as_ref().is_some_and(|s| Arc::ptr_eq(&s.handle.stop, &handle.stop)) {
+        *session = None;
+    }
+}
+
+// Ask the running session to stop; its thread notices it between captures
+pub fn request_stop<C>(slot: &SessionSlot<C>) {
+    if let Some(session) = slot.lock().unwrap().as_ref() {
+        session.handle.stop.store(true, Ordering::SeqCst);
+    }
+}
+
+pub fn info<C: Clone>(slot: &SessionSlot<C>) -> Option<SessionInfo<C>> {
+    slot.lock().unwrap().as_ref().map(|session| SessionInfo {
+        captures: session.handle.captures(),
+        output_folder: session.output_folder.to_string_lossy().to_string(),
+        config: session.config.clone(),
+    })
+}
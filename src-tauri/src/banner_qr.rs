@@ -0,0 +1,116 @@
+// QR code of the timestamp banner, for printed evidence: capture time, host, SHA-256 of the
+// captured pixels and an optional caption, as compact JSON. It is drawn next to the
+// timestamp text, black on white with its quiet zone, and the banner grows to fit it.
+
+use chrono::{DateTime, Local, SecondsFormat};
+use image::{Rgba, RgbaImage};
+use qrcode::{Color, EcLevel, QrCode};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+// Space between the code and the banner edges (pixels)
+pub const MARGIN: u32 = 8;
+// Light modules around the code, required by readers
+const QUIET_ZONE: u32 = 4;
+// Smaller modules do not survive printing or rescaling
+const MIN_MODULE_SIZE: u32 = 2;
+
+#[derive(Serialize, Debug)]
+pub struct QrPayload {
+    pub time: String, // RFC 3339, same instant as the banner text
+    pub host: String,
+    pub sha256: String, // RGBA pixels of the image, without the banner
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caption: Option<String>,
+}
+
+pub struct BannerQr {
+    modules: Vec<bool>, // Dark modules, row by row
+    width: u32,         // Modules per side, without the quiet zone
+    module_size: u32,   // Pixels per module
+}
+
+fn hostname() -> String {
+    ["COMPUTERNAME", "HOSTNAME"]
+        .iter()
+        .find_map(|name| std::env::var(name).ok())
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+pub fn payload(img: &RgbaImage, time: &DateTime<Local>, caption: Option<&str>) -> QrPayload {
+    QrPayload {
+        time: time.to_rfc3339_opts(SecondsFormat::Secs, false),
+        host: hostname(),
+        sha256: format!("{:x}", Sha256::digest(img.as_raw())),
+        caption: caption.map(str::trim).filter(|c| !c.is_empty()).map(str::to_string),
+    }
+}
+
+impl BannerQr {
+    // Code of the payload, with modules scaled with the font size but small enough for the
+    // code to fit in `max_side` pixels; None when it cannot fit with readable modules
+    pub fn new(payload: &QrPayload, font_size: u32, max_side: u32) -> Result<Option<Self>, String> {
+        let json = serde_json::to_string(payload).map_err(|e| e.to_string())?;
+        let code = QrCode::with_error_correction_level(json.as_bytes(), EcLevel::M).map_err(|e| e.to_string())?;
+        let width = code.width() as u32;
+        let modules = code.to_colors().into_iter().map(|c| c == Color::Dark).collect();
+
+        let module_size = (font_size / 5).max(MIN_MODULE_SIZE).min(max_side / (width + 2 * QUIET_ZONE));
+        Ok((module_size >= MIN_MODULE_SIZE).then_some(Self { modules, width, module_size }))
+    }
+
+    // Size of the code with its quiet zone (pixels)
+    pub fn side(&self) -> u32 {
+        (self.width + 2 * QUIET_ZONE) * self.module_size
+    }
+
+    // Draw the code with its quiet zone, top-left corner at `x`/`y`
+    pub fn draw(&self, img: &mut RgbaImage, x: u32, y: u32) {
+        let black = Rgba([0, 0, 0, 255]);
+        let white = Rgba([255, 255, 255, 255]);
+        let side = self.side();
+        for py in 0..side.min(img.height().saturating_sub(y)) {
+            for px in 0..side.min(img.width().saturating_sub(x)) {
+                let mx = (px / self.module_size) as i64 - QUIET_ZONE as i64;
+                let my = (py / self.module_size) as i64 - QUIET_ZONE as i64;
+                let inside = mx >= 0 && my >= 0 && mx < self.width as i64 && my < self.width as i64;
+                let dark = inside && self.modules[(my * self.width as i64 + mx) as usize];
+                img.put_pixel(x + px, y + py, if dark { black } else { white });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_payload() -> QrPayload {
+        QrPayload {
+            time: "2026-01-04T12:00:00+01:00".to_string(),
+            host: "host".to_string(),
+            sha256: format!("{:x}", Sha256::digest(b"pixels")),
+            caption: Some("Case 42".to_string()),
+        }
+    }
+
+    #[test]
+    fn module_size_follows_the_font_size() {
+        let qr = BannerQr::new(&sample_payload(), 20, 1000).unwrap().unwrap();
+        assert_eq!(qr.module_size, 4);
+        assert_eq!(qr.side(), (qr.width + 2 * QUIET_ZONE) * 4);
+    }
+
+    #[test]
+    fn narrow_space_shrinks_modules_down_to_the_minimum() {
+        let qr = BannerQr::new(&sample_payload(), 20, 1000).unwrap().unwrap();
+        let modules = qr.width + 2 * QUIET_ZONE;
+        let shrunk = BannerQr::new(&sample_payload(), 20, modules * 2).unwrap().unwrap();
+        assert_eq!(shrunk.module_size, 2);
+        assert!(BannerQr::new(&sample_payload(), 20, modules * 2 - 1).unwrap().is_none());
+        assert!(BannerQr::new(&sample_payload(), 20, 0).unwrap().is_none());
+    }
+}
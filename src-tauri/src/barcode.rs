@@ -0,0 +1,107 @@
+// QR code and barcode decoding (QR, DataMatrix, Aztec, PDF417, EAN/UPC, Code 128...) of the
+// cropped selection, offline from the PendingScreenshot pixels. Decoded payloads can be
+// copied to the clipboard, and web links opened in the browser.
+
+use crate::AppState;
+use arboard::Clipboard;
+use chrono::Local;
+use rxing::RXingResult;
+use serde::Serialize;
+use std::time::Instant;
+use tauri::State;
+
+#[derive(Clone, Serialize, Debug)]
+pub struct DecodedCode {
+    pub format: String, // "qrcode", "data matrix", "ean 13"...
+    pub text: String,
+    #[serde(rename = "isUrl")]
+    pub is_url: bool,
+    pub points: Vec<[f32; 2]>, // Finder points, logical pixels relative to the selection
+}
+
+fn is_web_url(text: &str) -> bool {
+    let text = text.trim();
+    (text.starts_with("https://") || text.starts_with("http://")) && !text.contains(char::is_whitespace)
+}
+
+fn to_code(result: &RXingResult, scale: f32) -> DecodedCode {
+    DecodedCode {
+        format: result.getBarcodeFormat().to_string(),
+        text: result.getText().to_string(),
+        is_url: is_web_url(result.getText()),
+        points: result.getPoints().iter().map(|p| [p.x / scale, p.y / scale]).collect(),
+    }
+}
+
+// Every code of an image; light codes on a dark background are found by decoding the
+// inverted image when the first pass finds nothing
+pub fn decode(img: &image::RgbaImage, scale_factor: f32) -> Vec<DecodedCode> {
+    let (width, height) = img.dimensions();
+    let luma: Vec<u8> = img
+        .pixels()
+        .map(|p| ((299 * p[0] as u32 + 587 * p[1] as u32 + 114 * p[2] as u32) / 1000) as u8)
+        .collect();
+
+    let scale = if scale_factor > 0.0 { scale_factor } else { 1.0 };
+    let mut results = rxing::helpers::detect_multiple_in_luma(luma.clone(), width, height).unwrap_or_default();
+    if results.is_empty() {
+        let inverted = luma.iter().map(|v| 255 - v).collect();
+        results = rxing::helpers::detect_multiple_in_luma(inverted, width, height).unwrap_or_default();
+    }
+
+    let mut codes: Vec<DecodedCode> = Vec::new();
+    for result in &results {
+        // The same code can be reported by several readers
+        if !codes.iter().any(|c| c.text == result.getText()) {
+            codes.push(to_code(result, scale));
+        }
+    }
+    codes
+}
+
+#[tauri::command]
+pub async fn decode_selection_codes(state: State<'_, AppState>) -> Result<Vec<DecodedCode>, String> {
+    let (image_data, scale_factor) = {
+        let pending = state.pending_screenshot.lock().unwrap();
+        let screenshot = pending.as_ref().ok_or("No pending screenshot")?;
+        (screenshot.image_data.clone(), screenshot.scale_factor)
+    };
+    let img = image::load_from_memory(&image_data).map_err(|e| e.to_string())?.to_rgba8();
+
+    let started = Instant::now();
+    let codes = decode(&img, scale_factor);
+    println!(
+        "[PERF] {} Code decoding took {:?}: {} code(s) ({})",
+        Local::now().format("%H:%M:%S%.3f"),
+        started.elapsed(),
+        codes.len(),
+        codes.iter().map(|c| c.format.as_str()).collect::<Vec<_>>().join(", ")
+    );
+    Ok(codes)
+}
+
+#[tauri::command]
+pub async fn copy_code_text(text: String) -> Result<(), String> {
+    let mut clipboard = Clipboard::new().map_err(|e| e.to_string())?;
+    clipboard.set_text(text).map_err(|e| e.to_string())
+}
+
+// Open a decoded web link in the default browser (other payloads are never opened)
+#[tauri::command]
+pub async fn open_code_url(url: String) -> Result<(), String> {
+    let url = url.trim();
+    if !is_web_url(url) {
+        return Err(format!("Not a web link: {}", url));
+    }
+    println!("[LOG] {} Opening decoded link {}", Local::now().format("%H:%M:%S%.3f"), url);
+
+    #[cfg(target_os = "windows")]
+    let program = "explorer";
+    #[cfg(target_os = "macos")]
+    let program = "open";
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let program = "xdg-open";
+
+    std::process::Command::new(program).arg(url).spawn().map_err(|e| e.to_string())?;
+    Ok(())
+}
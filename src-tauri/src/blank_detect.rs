@@ -0,0 +1,182 @@
+// Blank capture detection: protected video players and some GPU overlays come out as
+// all-black frames. The frozen frame (and the selection) is checked for uniform content
+// and the dialog is warned, with a retry through a delay or the alternate capture method.
+
+use crate::capture::CaptureMethod;
+use crate::{delayed_capture, open_selection_window, reset_capture_state, selection, AppState, RawScreenshot, SelectionBounds};
+use chrono::Local;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+// Largest per-channel spread (0-255) of a uniform area
+const UNIFORM_TOLERANCE: u8 = 4;
+// Mean luma under which a uniform area is reported as black
+const BLACK_LUMA: u32 = 16;
+// Samples per axis, enough to find any content without reading every pixel
+const SAMPLES: u32 = 256;
+
+#[derive(Clone, Copy, Serialize, Debug, PartialEq, Eq)]
+pub enum BlankKind {
+    #[serde(rename = "black")]
+    Black,
+    #[serde(rename = "uniform")]
+    Uniform,
+}
+
+// Event payload of "capture-blank-warning"
+#[derive(Clone, Serialize, Debug)]
+pub struct BlankReport {
+    pub scope: String, // "frame" or "selection"
+    pub kind: BlankKind,
+    pub color: [u8; 3], // Mean color
+    #[serde(rename = "displayId")]
+    pub display_id: Option<u32>,
+}
+
+// Uniform content of a rectangle of the frame (physical pixels), with its mean color
+pub fn analyse(raw: &RawScreenshot, rect: &selection::PixelRect) -> Option<(BlankKind, [u8; 3])> {
+    if rect.width == 0 || rect.height == 0 {
+        return None;
+    }
+    let stride = raw.width as usize * 4;
+    let step_x = (rect.width / SAMPLES).max(1);
+    let step_y = (rect.height / SAMPLES).max(1);
+
+    let (mut min, mut max, mut sum) = ([u8::MAX; 3], [0u8; 3], [0u64; 3]);
+    let mut count = 0u64;
+    for y in (rect.y..rect.y + rect.height).step_by(step_y as usize) {
+        for x in (rect.x..rect.x + rect.width).step_by(step_x as usize) {
+            let index = y as usize * stride + x as usize * 4;
+            for channel in 0..3 {
+                let value = raw.data[index + channel];
+                min[channel] = min[channel].min(value);
+                max[channel] = max[channel].max(value);
+                sum[channel] += value as u64;
+            }
+            count += 1;
+            // Stop at the first sign of content
+            if (0..3).any(|c| max[c] - min[c] > UNIFORM_TOLERANCE) {
+                return None;
+            }
+        }
+    }
+
+    let color = [0, 1, 2].map(|c| (sum[c] / count.max(1)) as u8);
+    let luma = (299 * color[0] as u32 + 587 * color[1] as u32 + 114 * color[2] as u32) / 1000;
+    let kind = if luma <= BLACK_LUMA { BlankKind::Black } else { BlankKind::Uniform };
+    Some((kind, color))
+}
+
+fn whole_frame(raw: &RawScreenshot) -> selection::PixelRect {
+    selection::PixelRect { x: 0, y: 0, width: raw.width, height: raw.height }
+}
+
+fn log_report(report: &BlankReport, method: CaptureMethod) {
+    println!(
+        "[LOG] {} Blank capture detected: {:?} {} (color {:?}, display {:?}, method {:?})",
+        Local::now().format("%H:%M:%S%.3f"),
+        report.kind,
+        report.scope,
+        report.color,
+        report.display_id,
+        method
+    );
+}
+
+// Check a freshly captured frame; the report is kept for the dialog, which asks for it
+// once loaded (the event may be sent before it listens)
+pub fn check_frame(state: &AppState, raw: &RawScreenshot, display_id: u32, method: CaptureMethod) -> Option<BlankReport> {
+    let report = analyse(raw, &whole_frame(raw)).map(|(kind, color)| BlankReport {
+        scope: "frame".to_string(),
+        kind,
+        color,
+        display_id: Some(display_id),
+    });
+    if let Some(report) = &report {
+        log_report(report, method);
+    }
+    *state.capture_warning.lock().unwrap() = report.clone();
+    report
+}
+
+pub fn notify(app: &AppHandle, report: &BlankReport) {
+    let _ = app.emit("capture-blank-warning", report.clone());
+}
+
+// Warning of the current capture, if its frame is blank
+#[tauri::command]
+pub async fn get_capture_warning(state: State<'_, AppState>) -> Result<Option<BlankReport>, String> {
+    Ok(state.capture_warning.lock().unwrap().clone())
+}
+
+// Check the selection of the frozen frame (logical pixels); warns the dialog when blank
+#[tauri::command]
+pub async fn check_selection_blank(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    bounds: SelectionBounds,
+) -> Result<Option<BlankReport>, String> {
+    let report = {
+        let current = state.current_screenshot.lock().unwrap();
+        let raw = current.as_ref().ok_or("No screenshot available")?;
+        let rect = selection::to_physical_rect(&bounds, raw.scale_factor, raw.width, raw.height)
+            .map_err(|e| e.to_string())?;
+        analyse(raw, &rect).map(|(kind, color)| BlankReport {
+            scope: "selection".to_string(),
+            kind,
+            color,
+            display_id: *state.current_display.lock().unwrap(),
+        })
+    };
+    if let Some(report) = &report {
+        log_report(report, CaptureMethod::Default);
+        notify(&app, report);
+    }
+    Ok(report)
+}
+
+// Drop the current capture and take it again, after a countdown or with another method
+#[tauri::command]
+pub async fn retry_capture(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    method: Option<CaptureMethod>,
+    delay_secs: Option<u32>,
+) -> Result<(), String> {
+    let method = method.unwrap_or_default();
+    let delay_secs = delay_secs.unwrap_or(0);
+    println!(
+        "[LOG] {} Retrying capture (method {:?}, delay {} s)",
+        Local::now().format("%H:%M:%S%.3f"),
+        method,
+        delay_secs
+    );
+
+    reset_capture_state(&app, &state);
+    *state.capture_method.lock().unwrap() = method;
+    for label in ["selection", "filename-dialog"] {
+        if let Some(window) = app.get_webview_window(label) {
+            window.close().map_err(|e| e.to_string())?;
+        }
+    }
+
+    std::thread::spawn(move || {
+        // The selection window must be gone before a new one can be opened
+        let started = Instant::now();
+        while app.get_webview_window("selection").is_some() && started.elapsed() < Duration::from_secs(2) {
+            std::thread::sleep(Duration::from_millis(20));
+        }
+
+        let result = if delay_secs > 0 {
+            delayed_capture::start(&app, delay_secs)
+        } else {
+            let state: State<'_, AppState> = app.state();
+            open_selection_window(&app, &state)
+        };
+        if let Err(e) = result {
+            println!("[LOG] {} Capture retry failed: {}", Local::now().format("%H:%M:%S%.3f"), e);
+        }
+    });
+    Ok(())
+}
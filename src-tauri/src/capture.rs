@@ -0,0 +1,426 @@
+// Capture backends: the real screen (via the `screenshots` crate) and a
+// file-backed source that replays image files, used to run the capture
+// pipeline headless (CI machines without a display).
+
+use crate::{cursor, RawScreenshot, SelectionBounds};
+use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// Environment variable selecting the file-backed source.
+// Holds a list of image paths separated like PATH (`;` on Windows, `:` elsewhere).
+pub const CAPTURE_FILES_ENV: &str = "SMART_PRTSCR_CAPTURE_FILES";
+
+#[derive(Clone, Serialize, Debug)]
+pub struct DisplayInfo {
+    pub id: u32,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    #[serde(rename = "scaleFactor")]
+    pub scale_factor: f32,
+    #[serde(rename = "isPrimary")]
+    pub is_primary: bool,
+}
+
+// How a whole display is captured: the usual backend, or a second code path offered
+// when the first one returned a black frame (protected video, GPU overlays)
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq, Default)]
+pub enum CaptureMethod {
+    #[serde(rename = "default")]
+    #[default]
+    Default,
+    #[serde(rename = "alternate")]
+    Alternate,
+}
+
+pub trait CaptureSource: Send + Sync {
+    // List the available displays, in enumeration order (the selection window captures the first one)
+    fn displays(&self) -> Result<Vec<DisplayInfo>, String>;
+
+    // Capture a whole display
+    fn capture_display(&self, display: &DisplayInfo) -> Result<RawScreenshot, String>;
+
+    // Capture an area of a display (logical pixels relative to the display, like SelectionBounds)
+    fn capture_area(
+        &self,
+        display: &DisplayInfo,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+    ) -> Result<RawScreenshot, String>;
+
+    // Capture a whole display through another code path, when the source has one
+    fn capture_display_alternate(&self, _display: &DisplayInfo) -> Result<RawScreenshot, String> {
+        Err("No alternate capture method for this source".to_string())
+    }
+}
+
+// Capture a whole display with the given method
+pub fn capture_display_with(
+    source: &dyn CaptureSource,
+    display: &DisplayInfo,
+    method: CaptureMethod,
+) -> Result<RawScreenshot, String> {
+    match method {
+        CaptureMethod::Default => source.capture_display(display),
+        CaptureMethod::Alternate => source.capture_display_alternate(display),
+    }
+}
+
+// Pick the capture source: file-backed if SMART_PRTSCR_CAPTURE_FILES is set, real screen otherwise
+pub fn default_source() -> Box<dyn CaptureSource> {
+    if let Some(value) = std::env::var_os(CAPTURE_FILES_ENV) {
+        let frames: Vec<PathBuf> = std::env::split_paths(&value).collect();
+        if !frames.is_empty() {
+            println!("[CAPTURE] Using file capture source ({} frames)", frames.len());
+            return Box::new(FileCaptureSource::new(frames));
+        }
+    }
+    Box::new(ScreenCaptureSource)
+}
+
+// Display with the given id, or the primary display when no id is given
+pub fn find_display(source: &dyn CaptureSource, id: Option<u32>) -> Result<DisplayInfo, String> {
+    let displays = source.displays()?;
+    match id {
+        Some(id) => displays
+            .into_iter()
+            .find(|d| d.id == id)
+            .ok_or_else(|| format!("Display {} not found", id)),
+        // Headless captures without a display default to the primary one, not the first enumerated
+        None => displays
+            .iter()
+            .find(|d| d.is_primary)
+            .or(displays.first())
+            .cloned()
+            .ok_or_else(|| "No screen found".to_string()),
+    }
+}
+
+// Capture a region (logical pixels relative to the display) or the whole display, with the
+// cursor drawn in when the options ask for it
+pub fn capture_region(
+    source: &dyn CaptureSource,
+    display_id: Option<u32>,
+    region: Option<&SelectionBounds>,
+    cursor: cursor::CursorOptions,
+) -> Result<RgbaImage, String> {
+    let mut raw = capture_region_raw(source, display_id, region)?;
+    if cursor.include {
+        cursor::composite(&mut raw, cursor.halo);
+    }
+    into_image(raw)
+}
+
+// Same capture, the cursor (if any) kept apart from the pixels
+pub fn capture_region_raw(
+    source: &dyn CaptureSource,
+    display_id: Option<u32>,
+    region: Option<&SelectionBounds>,
+) -> Result<RawScreenshot, String> {
+    let display = find_display(source, display_id)?;
+    match region {
+        Some(region) => {
+            if region.width <= 0 || region.height <= 0 {
+                return Err(format!("Region is empty ({}x{})", region.width, region.height));
+            }
+            source.capture_area(&display, region.x, region.y, region.width as u32, region.height as u32)
+        }
+        None => source.capture_display(&display),
+    }
+}
+
+pub fn into_image(raw: RawScreenshot) -> Result<RgbaImage, String> {
+    RgbaImage::from_raw(raw.width, raw.height, raw.data)
+        .ok_or_else(|| "Failed to create image from raw data".to_string())
+}
+
+// Real screen capture through the `screenshots` crate
+pub struct ScreenCaptureSource;
+
+impl ScreenCaptureSource {
+    fn find_screen(display: &DisplayInfo) -> Result<screenshots::Screen, String> {
+        screenshots::Screen::all()
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .find(|screen| screen.display_info.id == display.id)
+            .ok_or_else(|| format!("Display {} not found", display.id))
+    }
+}
+
+impl CaptureSource for ScreenCaptureSource {
+    fn displays(&self) -> Result<Vec<DisplayInfo>, String> {
+        let screens = screenshots::Screen::all().map_err(|e| e.to_string())?;
+        let displays: Vec<DisplayInfo> = screens
+            .iter()
+            .map(|screen| {
+                let info = screen.display_info;
+                DisplayInfo {
+                    id: info.id,
+                    x: info.x,
+                    y: info.y,
+                    width: info.width,
+                    height: info.height,
+                    scale_factor: info.scale_factor,
+                    is_primary: info.is_primary,
+                }
+            })
+            .collect();
+        Ok(displays)
+    }
+
+    fn capture_display(&self, display: &DisplayInfo) -> Result<RawScreenshot, String> {
+        let screen = Self::find_screen(display)?;
+        let capture = screen.capture().map_err(|e| e.to_string())?;
+        let (width, height) = capture.dimensions();
+        Ok(RawScreenshot {
+            width,
+            height,
+            data: capture.into_raw(),
+            scale_factor: display.scale_factor,
+            cursor: cursor::grab(display.x, display.y, width, height),
+        })
+    }
+
+    fn capture_area(
+        &self,
+        display: &DisplayInfo,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+    ) -> Result<RawScreenshot, String> {
+        let screen = Self::find_screen(display)?;
+        let capture = screen.capture_area(x, y, width, height).map_err(|e| e.to_string())?;
+        // Top-left corner of the area in screen pixels
+        let origin_x = display.x + (x as f32 * display.scale_factor).round() as i32;
+        let origin_y = display.y + (y as f32 * display.scale_factor).round() as i32;
+        Ok(RawScreenshot {
+            width: capture.width(),
+            height: capture.height(),
+            cursor: cursor::grab(origin_x, origin_y, capture.width(), capture.height()),
+            data: capture.into_raw(),
+            scale_factor: display.scale_factor,
+        })
+    }
+
+    fn capture_display_alternate(&self, display: &DisplayInfo) -> Result<RawScreenshot, String> {
+        // Physical size of the display
+        let width = (display.width as f32 * display.scale_factor).round() as u32;
+        let height = (display.height as f32 * display.scale_factor).round() as u32;
+        let data = alternate::capture(display.x, display.y, width, height)?;
+        Ok(RawScreenshot {
+            width,
+            height,
+            data,
+            scale_factor: display.scale_factor,
+            cursor: cursor::grab(display.x, display.y, width, height),
+        })
+    }
+}
+
+// Alternate capture path, returns RGBA pixels of a screen rectangle (screen pixels).
+// X11: plain GetImage on the root window; Windows: GDI BitBlt with CAPTUREBLT, which
+// also copies layered windows.
+#[cfg(target_os = "linux")]
+mod alternate {
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{ConnectionExt, ImageFormat, ImageOrder};
+
+    fn x11_error(e: impl std::fmt::Display) -> String {
+        format!("X11: {}", e)
+    }
+
+    pub fn capture(x: i32, y: i32, width: u32, height: u32) -> Result<Vec<u8>, String> {
+        let (conn, screen_num) = x11rb::connect(None).map_err(x11_error)?;
+        let setup = conn.setup();
+        let screen = &setup.roots[screen_num];
+        let bits_per_pixel = setup
+            .pixmap_formats
+            .iter()
+            .find(|format| format.depth == screen.root_depth)
+            .map(|format| format.bits_per_pixel);
+        if bits_per_pixel != Some(32) || setup.image_byte_order != ImageOrder::LSB_FIRST {
+            return Err(format!("X11: unsupported pixel format (depth {})", screen.root_depth));
+        }
+
+        let reply = conn
+            .get_image(ImageFormat::Z_PIXMAP, screen.root, x as i16, y as i16, width as u16, height as u16, !0)
+            .map_err(x11_error)?
+            .reply()
+            .map_err(x11_error)?;
+
+        // BGRX to RGBA
+        let mut data = reply.data;
+        for pixel in data.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+            pixel[3] = 255;
+        }
+        Ok(data)
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod alternate {
+    use windows::Win32::Graphics::Gdi::{
+        BitBlt, CreateCompatibleDC, CreateDIBSection, DeleteDC, DeleteObject, GetDC, ReleaseDC, SelectObject,
+        BITMAPINFO, BITMAPINFOHEADER, BI_RGB, CAPTUREBLT, DIB_RGB_COLORS, ROP_CODE, SRCCOPY,
+    };
+
+    pub fn capture(x: i32, y: i32, width: u32, height: u32) -> Result<Vec<u8>, String> {
+        unsafe {
+            let screen_dc = GetDC(None);
+            let memory_dc = CreateCompatibleDC(screen_dc);
+            let info = BITMAPINFO {
+                bmiHeader: BITMAPINFOHEADER {
+                    biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+                    biWidth: width as i32,
+                    biHeight: -(height as i32), // Top-down
+                    biPlanes: 1,
+                    biBitCount: 32,
+                    biCompression: BI_RGB.0,
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let mut bits: *mut std::ffi::c_void = std::ptr::null_mut();
+            let result = CreateDIBSection(memory_dc, &info, DIB_RGB_COLORS, &mut bits, None, 0).and_then(|bitmap| {
+                let previous = SelectObject(memory_dc, bitmap);
+                let copied = BitBlt(
+                    memory_dc,
+                    0,
+                    0,
+                    width as i32,
+                    height as i32,
+                    screen_dc,
+                    x,
+                    y,
+                    ROP_CODE(SRCCOPY.0 | CAPTUREBLT.0),
+                );
+                // BGRA to RGBA
+                let mut data = std::slice::from_raw_parts(bits as *const u8, (width * height * 4) as usize).to_vec();
+                for pixel in data.chunks_exact_mut(4) {
+                    pixel.swap(0, 2);
+                    pixel[3] = 255;
+                }
+                SelectObject(memory_dc, previous);
+                let _ = DeleteObject(bitmap);
+                copied.map(|_| data)
+            });
+            let _ = DeleteDC(memory_dc);
+            ReleaseDC(None, screen_dc);
+            result.map_err(|e| e.to_string())
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+mod alternate {
+    pub fn capture(_x: i32, _y: i32, _width: u32, _height: u32) -> Result<Vec<u8>, String> {
+        Err("No alternate capture method on this platform".to_string())
+    }
+}
+
+// Deterministic source serving frames from image files.
+// Exposes a single display sized after the first frame; each capture returns the
+// next frame in order and keeps returning the last one once the list is exhausted.
+pub struct FileCaptureSource {
+    frames: Vec<PathBuf>,
+    next_frame: AtomicUsize,
+    scale_factor: f32,
+}
+
+impl FileCaptureSource {
+    pub fn new(frames: Vec<PathBuf>) -> Self {
+        Self {
+            frames,
+            next_frame: AtomicUsize::new(0),
+            scale_factor: 1.0,
+        }
+    }
+
+    pub fn with_scale_factor(mut self, scale_factor: f32) -> Self {
+        self.scale_factor = scale_factor;
+        self
+    }
+
+    fn load_frame(&self, index: usize) -> Result<RawScreenshot, String> {
+        let path = self.frames.get(index).ok_or("No frame available")?;
+        let img = image::open(path)
+            .map_err(|e| format!("{}: {}", path.display(), e))?
+            .to_rgba8();
+        Ok(RawScreenshot {
+            width: img.width(),
+            height: img.height(),
+            data: img.into_raw(),
+            scale_factor: self.scale_factor,
+            cursor: None,
+        })
+    }
+
+    fn next(&self) -> Result<RawScreenshot, String> {
+        let last = self.frames.len().saturating_sub(1);
+        let index = self
+            .next_frame
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |i| Some((i + 1).min(last)))
+            .unwrap_or(last);
+        self.load_frame(index)
+    }
+}
+
+impl CaptureSource for FileCaptureSource {
+    fn displays(&self) -> Result<Vec<DisplayInfo>, String> {
+        // Display size is expressed in logical pixels, like the real displays
+        let (width, height) = match self.frames.first() {
+            Some(path) => image::image_dimensions(path).map_err(|e| format!("{}: {}", path.display(), e))?,
+            None => return Err("No frame available".to_string()),
+        };
+        Ok(vec![DisplayInfo {
+            id: 0,
+            x: 0,
+            y: 0,
+            width: (width as f32 / self.scale_factor).round() as u32,
+            height: (height as f32 / self.scale_factor).round() as u32,
+            scale_factor: self.scale_factor,
+            is_primary: true,
+        }])
+    }
+
+    fn capture_display(&self, _display: &DisplayInfo) -> Result<RawScreenshot, String> {
+        self.next()
+    }
+
+    fn capture_area(
+        &self,
+        _display: &DisplayInfo,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+    ) -> Result<RawScreenshot, String> {
+        let frame = self.next()?;
+        let img = RgbaImage::from_raw(frame.width, frame.height, frame.data)
+            .ok_or("Failed to create image from raw data")?;
+        // Frames are physical pixels, the area is logical
+        let scale = self.scale_factor;
+        let x = (x.max(0) as f32 * scale).round() as u32;
+        let y = (y.max(0) as f32 * scale).round() as u32;
+        if x >= img.width() || y >= img.height() {
+            return Err("Capture area outside of the frame".to_string());
+        }
+        let width = ((width as f32 * scale).round() as u32).min(img.width() - x);
+        let height = ((height as f32 * scale).round() as u32).min(img.height() - y);
+        let area = image::imageops::crop_imm(&img, x, y, width, height).to_image();
+        Ok(RawScreenshot {
+            width: area.width(),
+            height: area.height(),
+            data: area.into_raw(),
+            scale_factor: frame.scale_factor,
+            cursor: None,
+        })
+    }
+}
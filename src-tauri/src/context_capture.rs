@@ -0,0 +1,135 @@
+// Context captures: instead of the cropped selection, the output is the whole frozen frame
+// with the selection emphasised, either by dimming (and optionally blurring) everything
+// around it ("spotlight") or by outlining it ("outline"). The surroundings are prepared in
+// process_selection; the selection itself stays the pending image, so redactions,
+// annotations, OCR and code decoding keep working on it, and it is pasted back into the
+// frame when the capture is saved or copied.
+
+use crate::selection::PixelRect;
+use crate::RawScreenshot;
+use image::{imageops, Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+// Darkening of the surroundings in spotlight mode
+const DIM_OPACITY: f32 = 0.55;
+// Downscaling factor of the blurred surroundings (logical pixels per sample)
+const BLUR_FACTOR: u32 = 12;
+// Outline around the selection, logical pixels
+const OUTLINE_WIDTH: f32 = 4.0;
+const OUTLINE_COLOR: [u8; 3] = [255, 59, 48];
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq)]
+pub enum ContextMode {
+    #[serde(rename = "crop")]
+    #[default]
+    Crop, // Selection only
+    #[serde(rename = "spotlight")]
+    Spotlight,
+    #[serde(rename = "outline")]
+    Outline,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default)]
+pub struct ContextOptions {
+    #[serde(default)]
+    pub mode: ContextMode,
+    #[serde(default)]
+    pub blur: bool, // Spotlight: blur the dimmed surroundings too
+}
+
+// Whole frame with its surroundings emphasised; the selection goes at `x`/`y` (pixels)
+#[derive(Clone)]
+pub struct ContextFrame {
+    pub image: RgbaImage,
+    pub x: u32,
+    pub y: u32,
+}
+
+pub fn read_context_options(app: &AppHandle) -> ContextOptions {
+    app.store("settings.json")
+        .ok()
+        .and_then(|store| store.get("contextOptions"))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+fn dim(img: &mut RgbaImage) {
+    for pixel in img.pixels_mut() {
+        for channel in 0..3 {
+            pixel[channel] = (pixel[channel] as f32 * (1.0 - DIM_OPACITY)).round() as u8;
+        }
+    }
+}
+
+// Cheap strong blur: downscale then upscale with a linear filter
+fn blur(img: &RgbaImage, scale_factor: f32) -> RgbaImage {
+    let factor = ((BLUR_FACTOR as f32 * scale_factor).round() as u32).max(2);
+    let (width, height) = img.dimensions();
+    let small = imageops::resize(img, (width / factor).max(1), (height / factor).max(1), imageops::FilterType::Triangle);
+    imageops::resize(&small, width, height, imageops::FilterType::Triangle)
+}
+
+// Band of `thickness` pixels around the `x`/`y`/`width`/`height` rectangle, clipped to the image
+fn draw_outline(img: &mut RgbaImage, x: u32, y: u32, width: u32, height: u32, thickness: u32) {
+    let color = Rgba([OUTLINE_COLOR[0], OUTLINE_COLOR[1], OUTLINE_COLOR[2], 255]);
+    let left = x.saturating_sub(thickness);
+    let top = y.saturating_sub(thickness);
+    let right = (x + width + thickness).min(img.width());
+    let bottom = (y + height + thickness).min(img.height());
+    for py in top..bottom {
+        for px in left..right {
+            let inside = px >= x && px < x + width && py >= y && py < y + height;
+            if !inside {
+                img.put_pixel(px, py, color);
+            }
+        }
+    }
+}
+
+// Surroundings of the selection for the chosen mode, None when only the selection is kept
+// (`rect` is the selection in physical pixels of the frame)
+pub fn prepare(raw: &RawScreenshot, rect: &PixelRect, options: ContextOptions) -> Result<Option<ContextFrame>, String> {
+    if options.mode == ContextMode::Crop {
+        return Ok(None);
+    }
+    let mut image = RgbaImage::from_raw(raw.width, raw.height, raw.data.clone()).ok_or("Failed to create image from raw data")?;
+
+    match options.mode {
+        ContextMode::Spotlight => {
+            if options.blur {
+                image = blur(&image, raw.scale_factor);
+            }
+            dim(&mut image);
+        }
+        ContextMode::Outline => {
+            let thickness = ((OUTLINE_WIDTH * raw.scale_factor).round() as u32).max(1);
+            draw_outline(&mut image, rect.x, rect.y, rect.width, rect.height, thickness);
+        }
+        ContextMode::Crop => {}
+    }
+    Ok(Some(ContextFrame { image, x: rect.x, y: rect.y }))
+}
+
+impl ContextFrame {
+    // Frame with the (redacted and annotated) selection pasted back in place
+    pub fn compose(&self, selection: &RgbaImage) -> RgbaImage {
+        let mut image = self.image.clone();
+        imageops::replace(&mut image, selection, self.x as i64, self.y as i64);
+        image
+    }
+}
+
+#[tauri::command]
+pub async fn get_context_options(app: AppHandle) -> Result<ContextOptions, String> {
+    Ok(read_context_options(&app))
+}
+
+#[tauri::command]
+pub async fn set_context_options(app: AppHandle, options: ContextOptions) -> Result<ContextOptions, String> {
+    let store = app.store("settings.json").map_err(|e| e.to_string())?;
+    store.set("contextOptions", serde_json::to_value(options).unwrap());
+    store.save().map_err(|e| e.to_string())?;
+    Ok(options)
+}
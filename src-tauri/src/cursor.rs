@@ -0,0 +1,276 @@
+// Mouse cursor in captures: the cursor image and position are grabbed together with the
+// frame (XFixes on X11, GetCursorInfo on Windows) and kept in the RawScreenshot, so the
+// dialog can decide per capture whether it is composited, with an optional halo. Captures
+// without the selection window composite it in capture::capture_region, following the
+// stored options.
+
+use crate::RawScreenshot;
+use chrono::Local;
+use image::{Rgba, RgbaImage};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+// Halo around the hotspot, logical pixels
+const HALO_RADIUS: f32 = 22.0;
+const HALO_COLOR: [u8; 3] = [255, 214, 0];
+const HALO_OPACITY: f32 = 0.4;
+
+// Cursor image; `x`/`y` is the hotspot position (screen pixels when grabbed, frame
+// pixels once attached to a RawScreenshot)
+#[derive(Clone, Debug)]
+pub struct CursorSprite {
+    pub image: RgbaImage,
+    pub x: i32,
+    pub y: i32,
+    pub hotspot_x: u32,
+    pub hotspot_y: u32,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default)]
+pub struct CursorOptions {
+    #[serde(default)]
+    pub include: bool,
+    #[serde(default)]
+    pub halo: bool,
+}
+
+pub fn read_cursor_options(app: &AppHandle) -> CursorOptions {
+    app.store("settings.json")
+        .ok()
+        .and_then(|store| store.get("cursorOptions"))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+// Cursor of a frame whose top-left corner is at `origin_x`/`origin_y` (screen pixels),
+// None when hidden, outside the frame or not available on this platform
+pub fn grab(origin_x: i32, origin_y: i32, width: u32, height: u32) -> Option<CursorSprite> {
+    let mut sprite = match platform::grab() {
+        Ok(sprite) => sprite?,
+        Err(e) => {
+            println!("[LOG] {} Cursor capture failed: {}", Local::now().format("%H:%M:%S%.3f"), e);
+            return None;
+        }
+    };
+    sprite.x -= origin_x;
+    sprite.y -= origin_y;
+
+    // Keep it if any part of the cursor is inside the frame
+    let left = sprite.x - sprite.hotspot_x as i32;
+    let top = sprite.y - sprite.hotspot_y as i32;
+    let visible = left < width as i32
+        && top < height as i32
+        && left + sprite.image.width() as i32 > 0
+        && top + sprite.image.height() as i32 > 0;
+    visible.then_some(sprite)
+}
+
+fn blend(pixel: &mut [u8], color: [u8; 3], alpha: f32) {
+    for channel in 0..3 {
+        pixel[channel] = (color[channel] as f32 * alpha + pixel[channel] as f32 * (1.0 - alpha)).round() as u8;
+    }
+}
+
+// Draw the cursor of the frame (and its halo) into the frame itself
+pub fn composite(raw: &mut RawScreenshot, halo: bool) {
+    let Some(sprite) = raw.cursor.take() else {
+        return;
+    };
+    let (width, height) = (raw.width as i32, raw.height as i32);
+    let stride = raw.width as usize * 4;
+
+    if halo {
+        let radius = HALO_RADIUS * if raw.scale_factor > 0.0 { raw.scale_factor } else { 1.0 };
+        let reach = radius.ceil() as i32 + 1;
+        for y in (sprite.y - reach).max(0)..(sprite.y + reach).min(height) {
+            for x in (sprite.x - reach).max(0)..(sprite.x + reach).min(width) {
+                let distance = (((x - sprite.x).pow(2) + (y - sprite.y).pow(2)) as f32).sqrt();
+                // One pixel of anti-aliasing on the edge
+                let coverage = (radius - distance + 0.5).clamp(0.0, 1.0);
+                if coverage > 0.0 {
+                    let index = y as usize * stride + x as usize * 4;
+                    blend(&mut raw.data[index..index + 4], HALO_COLOR, HALO_OPACITY * coverage);
+                }
+            }
+        }
+    }
+
+    let left = sprite.x - sprite.hotspot_x as i32;
+    let top = sprite.y - sprite.hotspot_y as i32;
+    for (cx, cy, &Rgba([r, g, b, a])) in sprite.image.enumerate_pixels() {
+        let (x, y) = (left + cx as i32, top + cy as i32);
+        if a == 0 || x < 0 || y < 0 || x >= width || y >= height {
+            continue;
+        }
+        let index = y as usize * stride + x as usize * 4;
+        blend(&mut raw.data[index..index + 4], [r, g, b], a as f32 / 255.0);
+    }
+
+    raw.cursor = Some(sprite);
+}
+
+#[tauri::command]
+pub async fn get_cursor_options(app: AppHandle) -> Result<CursorOptions, String> {
+    Ok(read_cursor_options(&app))
+}
+
+#[tauri::command]
+pub async fn set_cursor_options(app: AppHandle, options: CursorOptions) -> Result<CursorOptions, String> {
+    let store = app.store("settings.json").map_err(|e| e.to_string())?;
+    store.set("cursorOptions", serde_json::to_value(options).unwrap());
+    store.save().map_err(|e| e.to_string())?;
+    Ok(options)
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::CursorSprite;
+    use image::RgbaImage;
+    use x11rb::protocol::xfixes::ConnectionExt;
+
+    fn x11_error(e: impl std::fmt::Display) -> String {
+        format!("X11: {}", e)
+    }
+
+    pub fn grab() -> Result<Option<CursorSprite>, String> {
+        let (conn, _) = x11rb::connect(None).map_err(x11_error)?;
+        // XFixes requires the version negotiation before any other request
+        conn.xfixes_query_version(4, 0).map_err(x11_error)?.reply().map_err(x11_error)?;
+        let reply = conn.xfixes_get_cursor_image().map_err(x11_error)?.reply().map_err(x11_error)?;
+        if reply.width == 0 || reply.height == 0 {
+            return Ok(None);
+        }
+
+        // Premultiplied ARGB, one u32 per pixel
+        let mut data = Vec::with_capacity(reply.cursor_image.len() * 4);
+        for argb in &reply.cursor_image {
+            let alpha = (argb >> 24) as u8;
+            let unpremultiply = |c: u32| {
+                if alpha == 0 { 0 } else { ((c & 0xff) * 255 / alpha as u32).min(255) as u8 }
+            };
+            data.extend_from_slice(&[unpremultiply(argb >> 16), unpremultiply(argb >> 8), unpremultiply(*argb), alpha]);
+        }
+        let image = RgbaImage::from_raw(reply.width as u32, reply.height as u32, data)
+            .ok_or("Invalid cursor image")?;
+
+        Ok(Some(CursorSprite {
+            image,
+            x: reply.x as i32,
+            y: reply.y as i32,
+            hotspot_x: reply.xhot as u32,
+            hotspot_y: reply.yhot as u32,
+        }))
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::CursorSprite;
+    use image::RgbaImage;
+    use windows::Win32::Graphics::Gdi::{
+        CreateCompatibleDC, CreateDIBSection, DeleteDC, DeleteObject, GetObjectW, SelectObject, BITMAP, BITMAPINFO,
+        BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::{
+        DrawIconEx, GetCursorInfo, GetIconInfo, CURSORINFO, CURSOR_SHOWING, DI_NORMAL, HICON, ICONINFO,
+    };
+
+    // Draw the cursor over a uniform background, returns BGRA pixels
+    unsafe fn draw(icon: HICON, width: i32, height: i32, background: u8) -> Result<Vec<u8>, String> {
+        let dc = CreateCompatibleDC(None);
+        let info = BITMAPINFO {
+            bmiHeader: BITMAPINFOHEADER {
+                biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+                biWidth: width,
+                biHeight: -height, // Top-down
+                biPlanes: 1,
+                biBitCount: 32,
+                biCompression: BI_RGB.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut bits: *mut std::ffi::c_void = std::ptr::null_mut();
+        let bitmap = match CreateDIBSection(dc, &info, DIB_RGB_COLORS, &mut bits, None, 0) {
+            Ok(bitmap) => bitmap,
+            Err(e) => {
+                let _ = DeleteDC(dc);
+                return Err(e.to_string());
+            }
+        };
+        let previous = SelectObject(dc, bitmap);
+
+        let pixels = std::slice::from_raw_parts_mut(bits as *mut u8, (width * height * 4) as usize);
+        pixels.fill(background);
+        let result = DrawIconEx(dc, 0, 0, icon, width, height, 0, None, DI_NORMAL);
+        let copy = pixels.to_vec();
+
+        SelectObject(dc, previous);
+        let _ = DeleteObject(bitmap);
+        let _ = DeleteDC(dc);
+        result.map_err(|e| e.to_string())?;
+        Ok(copy)
+    }
+
+    pub fn grab() -> Result<Option<CursorSprite>, String> {
+        unsafe {
+            let mut cursor = CURSORINFO { cbSize: std::mem::size_of::<CURSORINFO>() as u32, ..Default::default() };
+            GetCursorInfo(&mut cursor).map_err(|e| e.to_string())?;
+            if cursor.flags.0 & CURSOR_SHOWING.0 == 0 || cursor.hCursor.is_invalid() {
+                return Ok(None);
+            }
+            let icon = HICON(cursor.hCursor.0);
+
+            let mut icon_info = ICONINFO::default();
+            GetIconInfo(icon, &mut icon_info).map_err(|e| e.to_string())?;
+
+            // Monochrome cursors stack the AND and XOR masks in one bitmap of double height
+            let mut bitmap = BITMAP::default();
+            let (source, monochrome) = if icon_info.hbmColor.is_invalid() {
+                (icon_info.hbmMask, true)
+            } else {
+                (icon_info.hbmColor, false)
+            };
+            GetObjectW(source, std::mem::size_of::<BITMAP>() as i32, Some(&mut bitmap as *mut BITMAP as *mut _));
+            let width = bitmap.bmWidth;
+            let height = if monochrome { bitmap.bmHeight / 2 } else { bitmap.bmHeight };
+            let _ = DeleteObject(icon_info.hbmMask);
+            if !icon_info.hbmColor.is_invalid() {
+                let _ = DeleteObject(icon_info.hbmColor);
+            }
+            if width <= 0 || height <= 0 {
+                return Ok(None);
+            }
+
+            // Drawn on black and on white: the difference gives the alpha channel
+            // (inverting cursors, like the text beam, come out as opaque white)
+            let on_black = draw(icon, width, height, 0)?;
+            let on_white = draw(icon, width, height, 255)?;
+            let mut data = Vec::with_capacity(on_black.len());
+            for (black, white) in on_black.chunks_exact(4).zip(on_white.chunks_exact(4)) {
+                let alpha = 255 - white[1].saturating_sub(black[1]);
+                let color = |c: u8| if alpha == 0 { 0 } else { (c as u32 * 255 / alpha as u32).min(255) as u8 };
+                data.extend_from_slice(&[color(black[2]), color(black[1]), color(black[0]), alpha]);
+            }
+            let image = RgbaImage::from_raw(width as u32, height as u32, data).ok_or("Invalid cursor image")?;
+
+            Ok(Some(CursorSprite {
+                image,
+                x: cursor.ptScreenPos.x,
+                y: cursor.ptScreenPos.y,
+                hotspot_x: icon_info.xHotspot,
+                hotspot_y: icon_info.yHotspot,
+            }))
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+mod platform {
+    use super::CursorSprite;
+
+    pub fn grab() -> Result<Option<CursorSprite>, String> {
+        Ok(None)
+    }
+}
@@ -0,0 +1,186 @@
+// Delayed capture: counts down, then runs the normal freeze-and-select flow.
+// Gives time to open hover menus and tooltips, which PrintScreen would close.
+
+use crate::{open_selection_window, AppState, TRAY_ID, TRAY_TOOLTIP};
+use chrono::Local;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State, WebviewUrl, WebviewWindowBuilder};
+use tauri_plugin_store::StoreExt;
+
+pub const DEFAULT_DELAY_SECS: u32 = 5;
+pub const MAX_DELAY_SECS: u32 = 60;
+
+// Generation of the latest countdown; each countdown has its own window ("countdown-<n>"),
+// so one finishing while another starts cannot close the new window
+static LATEST_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+// Running countdown, kept in AppState
+pub struct Countdown {
+    generation: u64,
+    cancelled: Arc<AtomicBool>,
+}
+
+// Event payload sent every second while counting down
+#[derive(Clone, Serialize)]
+pub struct CountdownPayload {
+    pub remaining: u32,
+    pub total: u32,
+}
+
+// Delay configured for the hotkey and the "custom" tray entry
+pub fn get_stored_delay(app: &AppHandle) -> u32 {
+    app.store("settings.json")
+        .ok()
+        .and_then(|store| store.get("captureDelay"))
+        .and_then(|v| v.as_u64())
+        .map(|v| (v as u32).clamp(1, MAX_DELAY_SECS))
+        .unwrap_or(DEFAULT_DELAY_SECS)
+}
+
+// Start a countdown of `seconds`, replacing any countdown already running
+pub fn start(app: &AppHandle, seconds: u32) -> Result<(), String> {
+    if seconds == 0 || seconds > MAX_DELAY_SECS {
+        return Err(format!("Delay must be between 1 and {} seconds", MAX_DELAY_SECS));
+    }
+    println!("[LOG] {} Delayed capture started ({} s)", Local::now().format("%H:%M:%S%.3f"), seconds);
+
+    let generation = LATEST_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let previous = {
+        let state: State<'_, AppState> = app.state();
+        let mut active = state.delayed_capture.lock().unwrap();
+        active.replace(Countdown { generation, cancelled: cancelled.clone() })
+    };
+    if let Some(previous) = previous {
+        previous.cancelled.store(true, Ordering::SeqCst);
+        close_countdown_window(app, previous.generation);
+    }
+
+    open_countdown_window(app, generation)?;
+
+    let app = app.clone();
+    std::thread::spawn(move || {
+        for remaining in (1..=seconds).rev() {
+            if cancelled.load(Ordering::SeqCst) {
+                return;
+            }
+            let _ = app.emit("capture-countdown", CountdownPayload { remaining, total: seconds });
+            set_tray_tooltip(&app, &format!("Smart PrtScr - Capture dans {} s", remaining));
+            std::thread::sleep(Duration::from_secs(1));
+        }
+
+        // Stop here if cancelled during the last second, otherwise release the slot
+        {
+            let state: State<'_, AppState> = app.state();
+            let mut active = state.delayed_capture.lock().unwrap();
+            if cancelled.load(Ordering::SeqCst) {
+                return;
+            }
+            *active = None;
+        }
+
+        close_countdown_window(&app, generation);
+        // A countdown started in the meantime owns the tooltip and replaces this capture
+        if LATEST_GENERATION.load(Ordering::SeqCst) != generation {
+            return;
+        }
+        set_tray_tooltip(&app, TRAY_TOOLTIP);
+        // Let the compositor remove the countdown window before freezing the screen
+        std::thread::sleep(Duration::from_millis(crate::HIDE_REPAINT_DELAY_MS));
+
+        if LATEST_GENERATION.load(Ordering::SeqCst) != generation {
+            return;
+        }
+        println!("[LOG] {} Delayed capture countdown finished", Local::now().format("%H:%M:%S%.3f"));
+        let state: State<'_, AppState> = app.state();
+        if let Err(e) = open_selection_window(&app, &state) {
+            println!("[LOG] {} Delayed capture failed: {}", Local::now().format("%H:%M:%S%.3f"), e);
+        }
+    });
+
+    Ok(())
+}
+
+// Cancel the running countdown, if any
+pub fn cancel(app: &AppHandle) {
+    let state: State<'_, AppState> = app.state();
+    // Flag is set under the lock so the countdown thread cannot miss it
+    let previous = {
+        let mut active = state.delayed_capture.lock().unwrap();
+        let previous = active.take();
+        if let Some(countdown) = &previous {
+            countdown.cancelled.store(true, Ordering::SeqCst);
+        }
+        previous
+    };
+    if let Some(countdown) = previous {
+        println!("[LOG] {} Delayed capture cancelled", Local::now().format("%H:%M:%S%.3f"));
+        close_countdown_window(app, countdown.generation);
+        set_tray_tooltip(app, TRAY_TOOLTIP);
+        let _ = app.emit("capture-countdown-cancelled", ());
+    }
+}
+
+fn set_tray_tooltip(app: &AppHandle, tooltip: &str) {
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let _ = tray.set_tooltip(Some(tooltip));
+    }
+}
+
+fn window_label(generation: u64) -> String {
+    format!("countdown-{}", generation)
+}
+
+// Small always-on-top window showing the remaining seconds.
+// Not focused, so the menu or tooltip being captured stays open.
+fn open_countdown_window(app: &AppHandle, generation: u64) -> Result<(), String> {
+    WebviewWindowBuilder::new(app, window_label(generation), WebviewUrl::App("countdown.html".into()))
+        .title("Smart PrtScr - Countdown")
+        .inner_size(150.0, 70.0)
+        .position(20.0, 20.0)
+        .decorations(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .resizable(false)
+        .focused(false)
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+fn close_countdown_window(app: &AppHandle, generation: u64) {
+    if let Some(window) = app.get_webview_window(&window_label(generation)) {
+        let _ = window.close();
+    }
+}
+
+#[tauri::command]
+pub async fn start_delayed_capture(app: AppHandle, seconds: Option<u32>) -> Result<u32, String> {
+    let seconds = seconds.unwrap_or_else(|| get_stored_delay(&app));
+    start(&app, seconds)?;
+    Ok(seconds)
+}
+
+#[tauri::command]
+pub async fn cancel_delayed_capture(app: AppHandle) -> Result<(), String> {
+    cancel(&app);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_capture_delay(app: AppHandle) -> Result<u32, String> {
+    Ok(get_stored_delay(&app))
+}
+
+#[tauri::command]
+pub async fn set_capture_delay(app: AppHandle, seconds: u32) -> Result<u32, String> {
+    let seconds = seconds.clamp(1, MAX_DELAY_SECS);
+    let store = app.store("settings.json").map_err(|e| e.to_string())?;
+    store.set("captureDelay", serde_json::json!(seconds));
+    store.save().map_err(|e| e.to_string())?;
+    Ok(seconds)
+}
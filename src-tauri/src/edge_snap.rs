@@ -0,0 +1,186 @@
+// Smart snapping: rectangles found in the frozen frame (window borders, panels,
+// buttons) by edge detection and contour tracing, offered to the selection window.
+//
+// Detection runs once per capture session, right after the capture, and the result
+// is cached in AppState under the session token.
+
+use crate::selection::PixelRect;
+use crate::{AppState, RawScreenshot, SelectionBounds};
+use chrono::Local;
+use image::{GrayImage, Luma};
+use imageproc::contours::find_contours;
+use std::sync::Arc;
+use std::time::Instant;
+use tauri::{AppHandle, Manager, State};
+
+// Luma step between neighbouring pixels considered an edge
+const EDGE_THRESHOLD: i16 = 24;
+// Smallest rectangle kept (physical pixels)
+const MIN_SIZE: u32 = 12;
+// Fraction of the contour points that must lie on the bounding box border
+const MIN_RECTANGULARITY: f32 = 0.85;
+// Distance (physical pixels) under which a point is on the border, or two edges are the same
+const TOLERANCE: u32 = 2;
+// Most candidates returned for one cursor position
+const MAX_CANDIDATES: usize = 8;
+
+// Detected rectangles of one capture session
+pub struct SnapCache {
+    token: String,
+    scale_factor: f32,
+    rectangles: Arc<Vec<PixelRect>>,
+}
+
+fn luma(data: &[u8], index: usize) -> i16 {
+    // ITU-R BT.601 weights, integer version
+    ((299 * data[index] as u32 + 587 * data[index + 1] as u32 + 114 * data[index + 2] as u32) / 1000) as i16
+}
+
+// Binary edge map: a pixel is an edge when it differs from its right or bottom neighbour
+fn edge_map(raw: &RawScreenshot) -> GrayImage {
+    let (width, height) = (raw.width, raw.height);
+    let mut edges = GrayImage::new(width, height);
+    let stride = width as usize * 4;
+    for y in 0..height as usize {
+        for x in 0..width as usize {
+            let index = y * stride + x * 4;
+            let here = luma(&raw.data, index);
+            let right = if x + 1 < width as usize { luma(&raw.data, index + 4) } else { here };
+            let below = if y + 1 < height as usize { luma(&raw.data, index + stride) } else { here };
+            if (here - right).abs() > EDGE_THRESHOLD || (here - below).abs() > EDGE_THRESHOLD {
+                edges.put_pixel(x as u32, y as u32, Luma([255]));
+            }
+        }
+    }
+    edges
+}
+
+fn same_rect(a: &PixelRect, b: &PixelRect) -> bool {
+    a.x.abs_diff(b.x) <= TOLERANCE
+        && a.y.abs_diff(b.y) <= TOLERANCE
+        && (a.x + a.width).abs_diff(b.x + b.width) <= TOLERANCE
+        && (a.y + a.height).abs_diff(b.y + b.height) <= TOLERANCE
+}
+
+// Rectangular contours of the frame, smallest first, without near-duplicates
+pub fn detect_rectangles(raw: &RawScreenshot) -> Vec<PixelRect> {
+    let edges = edge_map(raw);
+    let mut rectangles: Vec<PixelRect> = Vec::new();
+
+    for contour in find_contours::<i32>(&edges) {
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (i32::MAX, i32::MAX, i32::MIN, i32::MIN);
+        for point in &contour.points {
+            min_x = min_x.min(point.x);
+            min_y = min_y.min(point.y);
+            max_x = max_x.max(point.x);
+            max_y = max_y.max(point.y);
+        }
+        let width = (max_x - min_x + 1) as u32;
+        let height = (max_y - min_y + 1) as u32;
+        if width < MIN_SIZE || height < MIN_SIZE {
+            continue;
+        }
+
+        // Keep contours that follow their bounding box (rectangles, not text or icons)
+        let tolerance = TOLERANCE as i32;
+        let on_border = contour
+            .points
+            .iter()
+            .filter(|p| {
+                (p.x - min_x).abs() <= tolerance
+                    || (p.x - max_x).abs() <= tolerance
+                    || (p.y - min_y).abs() <= tolerance
+                    || (p.y - max_y).abs() <= tolerance
+            })
+            .count();
+        if (on_border as f32) < contour.points.len() as f32 * MIN_RECTANGULARITY {
+            continue;
+        }
+
+        let rect = PixelRect { x: min_x as u32, y: min_y as u32, width, height };
+        if !rectangles.iter().any(|r| same_rect(r, &rect)) {
+            rectangles.push(rect);
+        }
+    }
+
+    rectangles.sort_by_key(|r| r.width as u64 * r.height as u64);
+    rectangles
+}
+
+// Detect the rectangles of the current frame if not cached yet for this session
+fn ensure_cache(state: &AppState) -> Result<(Arc<Vec<PixelRect>>, f32), String> {
+    let token = state.capture_session.lock().unwrap().clone().ok_or("No capture session")?;
+    if let Some(cache) = state.snap_cache.lock().unwrap().as_ref() {
+        if cache.token == token {
+            return Ok((cache.rectangles.clone(), cache.scale_factor));
+        }
+    }
+
+    // Work on a copy: the frozen frame lock is needed to serve the selection window
+    let raw = state.current_screenshot.lock().unwrap().clone().ok_or("No screenshot available")?;
+    let started = Instant::now();
+    let rectangles = Arc::new(detect_rectangles(&raw));
+    let scale_factor = raw.scale_factor;
+    println!(
+        "[PERF] {} detect_rectangles() took {:?} ({} rectangles)",
+        Local::now().format("%H:%M:%S%.3f"),
+        started.elapsed(),
+        rectangles.len()
+    );
+
+    // Only store the result if the capture did not change meanwhile
+    let mut cache = state.snap_cache.lock().unwrap();
+    if state.capture_session.lock().unwrap().as_deref() == Some(token.as_str()) {
+        *cache = Some(SnapCache { token, scale_factor, rectangles: rectangles.clone() });
+    }
+    Ok((rectangles, scale_factor))
+}
+
+// Run the detection in the background right after a capture, so the first query is instant
+pub fn warm_up(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let state: State<'_, AppState> = app.state();
+        if let Err(e) = ensure_cache(&state) {
+            println!("[LOG] {} Snap detection failed: {}", Local::now().format("%H:%M:%S%.3f"), e);
+        }
+    });
+}
+
+fn to_logical(rect: &PixelRect, scale_factor: f32) -> SelectionBounds {
+    let scale = if scale_factor > 0.0 { scale_factor } else { 1.0 };
+    SelectionBounds {
+        x: (rect.x as f32 / scale).round() as i32,
+        y: (rect.y as f32 / scale).round() as i32,
+        width: (rect.width as f32 / scale).round() as i32,
+        height: (rect.height as f32 / scale).round() as i32,
+    }
+}
+
+// Rectangles near a point (logical pixels relative to the display): those containing it,
+// smallest first, then those with a border within `radius`
+#[tauri::command]
+pub async fn find_snap_rectangles(
+    state: State<'_, AppState>,
+    x: i32,
+    y: i32,
+    radius: Option<i32>,
+) -> Result<Vec<SelectionBounds>, String> {
+    let (rectangles, scale_factor) = ensure_cache(&state)?;
+    let radius = radius.unwrap_or(8);
+
+    let mut containing = Vec::new();
+    let mut nearby = Vec::new();
+    for bounds in rectangles.iter().map(|r| to_logical(r, scale_factor)) {
+        let (right, bottom) = (bounds.x + bounds.width, bounds.y + bounds.height);
+        if x >= bounds.x && x < right && y >= bounds.y && y < bottom {
+            containing.push(bounds);
+        } else if x >= bounds.x - radius && x < right + radius && y >= bounds.y - radius && y < bottom + radius {
+            nearby.push(bounds);
+        }
+    }
+
+    containing.extend(nearby);
+    containing.truncate(MAX_CANDIDATES);
+    Ok(containing)
+}
@@ -0,0 +1,110 @@
+// Frame comparison for the region watcher: per-pixel luma difference with a
+// tolerance (ignores compression noise, anti-aliasing and cursor blinking),
+// and grouping of changed pixels into rectangles to outline them.
+
+use image::{Rgba, RgbaImage};
+use imageproc::drawing::draw_hollow_rect_mut;
+use imageproc::rect::Rect;
+
+// Side of the grid cells used to group changed pixels (pixels)
+const CELL_SIZE: u32 = 16;
+
+pub struct FrameDiff {
+    // Fraction of changed pixels (0.0 - 1.0)
+    pub changed_ratio: f32,
+    // Bounding rectangles of the changed areas
+    pub regions: Vec<Rect>,
+}
+
+fn luma(pixel: &Rgba<u8>) -> i32 {
+    // ITU-R BT.601 weights, integer version
+    (299 * pixel[0] as i32 + 587 * pixel[1] as i32 + 114 * pixel[2] as i32) / 1000
+}
+
+// Compare two frames; a size change counts as a full change
+pub fn compare(previous: &RgbaImage, current: &RgbaImage, tolerance: u8) -> FrameDiff {
+    let (width, height) = current.dimensions();
+    if previous.dimensions() != current.dimensions() || width == 0 || height == 0 {
+        return FrameDiff {
+            changed_ratio: 1.0,
+            regions: vec![Rect::at(0, 0).of_size(width.max(1), height.max(1))],
+        };
+    }
+
+    let cols = width.div_ceil(CELL_SIZE);
+    let rows = height.div_ceil(CELL_SIZE);
+    let mut changed_cells = vec![false; (cols * rows) as usize];
+    let mut changed_pixels: u64 = 0;
+
+    for (x, y, pixel) in current.enumerate_pixels() {
+        let before = previous.get_pixel(x, y);
+        if (luma(pixel) - luma(before)).unsigned_abs() > tolerance as u32 {
+            changed_pixels += 1;
+            changed_cells[((y / CELL_SIZE) * cols + x / CELL_SIZE) as usize] = true;
+        }
+    }
+
+    FrameDiff {
+        changed_ratio: changed_pixels as f32 / (width as u64 * height as u64) as f32,
+        regions: group_cells(&changed_cells, cols, rows, width, height),
+    }
+}
+
+// Merge adjacent changed cells (8-connectivity) into bounding rectangles
+fn group_cells(cells: &[bool], cols: u32, rows: u32, width: u32, height: u32) -> Vec<Rect> {
+    let mut visited = vec![false; cells.len()];
+    let mut regions = Vec::new();
+
+    for start in 0..cells.len() {
+        if !cells[start] || visited[start] {
+            continue;
+        }
+        visited[start] = true;
+        let mut stack = vec![start];
+        let (mut min_col, mut min_row) = (u32::MAX, u32::MAX);
+        let (mut max_col, mut max_row) = (0, 0);
+
+        while let Some(index) = stack.pop() {
+            let col = index as u32 % cols;
+            let row = index as u32 / cols;
+            min_col = min_col.min(col);
+            min_row = min_row.min(row);
+            max_col = max_col.max(col);
+            max_row = max_row.max(row);
+
+            for dy in -1i32..=1 {
+                for dx in -1i32..=1 {
+                    let (c, r) = (col as i32 + dx, row as i32 + dy);
+                    if c < 0 || r < 0 || c >= cols as i32 || r >= rows as i32 {
+                        continue;
+                    }
+                    let neighbour = (r as u32 * cols + c as u32) as usize;
+                    if cells[neighbour] && !visited[neighbour] {
+                        visited[neighbour] = true;
+                        stack.push(neighbour);
+                    }
+                }
+            }
+        }
+
+        let x = min_col * CELL_SIZE;
+        let y = min_row * CELL_SIZE;
+        let right = ((max_col + 1) * CELL_SIZE).min(width);
+        let bottom = ((max_row + 1) * CELL_SIZE).min(height);
+        regions.push(Rect::at(x as i32, y as i32).of_size(right - x, bottom - y));
+    }
+
+    regions
+}
+
+// Outline changed areas with a 2 px red rectangle
+pub fn outline_regions(img: &mut RgbaImage, regions: &[Rect]) {
+    let color = Rgba([255, 0, 0, 255]);
+    for region in regions {
+        draw_hollow_rect_mut(img, *region, color);
+        if region.width() > 2 && region.height() > 2 {
+            let inner = Rect::at(region.left() + 1, region.top() + 1).of_size(region.width() - 2, region.height() - 2);
+            draw_hollow_rect_mut(img, inner, color);
+        }
+    }
+}
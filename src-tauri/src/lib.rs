@@ -2,16 +2,15 @@ use ab_glyph::{Font, FontArc, PxScale};
 use chrono::Local;
 use image::{DynamicImage, ImageFormat, Rgba, RgbaImage};
 use imageproc::drawing::draw_text_mut;
-use screenshots::Screen;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::io::Cursor;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 use std::env;
 use tauri::{
     image::Image,
-    menu::{MenuBuilder, MenuItemBuilder},
+    menu::{Menu, MenuBuilder, MenuItemBuilder, SubmenuBuilder},
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
     AppHandle, Emitter, Listener, Manager, State, WebviewUrl, WebviewWindowBuilder,
 };
@@ -20,6 +19,32 @@ use tauri_plugin_dialog::DialogExt;
 use tauri_plugin_store::StoreExt;
 use arboard::{Clipboard, ImageData};
 
+pub mod annotations;
+pub mod auto_redact;
+pub mod banner_qr;
+pub mod background;
+pub mod barcode;
+pub mod blank_detect;
+pub mod capture;
+pub mod context_capture;
+pub mod cursor;
+pub mod delayed_capture;
+pub mod edge_snap;
+pub mod image_diff;
+pub mod ocr;
+pub mod presets;
+pub mod privacy;
+pub mod redaction;
+pub mod repeat_capture;
+pub mod scroll_capture;
+pub mod scheduler;
+pub mod selection;
+pub mod timelapse;
+pub mod watcher;
+pub mod window_list;
+
+use capture::CaptureSource;
+
 
 #[cfg(target_os = "windows")]
 use winreg::enums::*;
@@ -37,7 +62,7 @@ mod keyboard_hook {
         WNDCLASSW, MSG, WM_HOTKEY, WM_QUIT, WINDOW_EX_STYLE, WINDOW_STYLE,
     };
     use windows::Win32::UI::Input::KeyboardAndMouse::{
-        RegisterHotKey, UnregisterHotKey, MOD_WIN, MOD_SHIFT, MOD_NOREPEAT,
+        RegisterHotKey, UnregisterHotKey, MOD_WIN, MOD_SHIFT, MOD_CONTROL, MOD_NOREPEAT,
     };
     use windows::core::PCWSTR;
     use std::sync::OnceLock;
@@ -49,6 +74,8 @@ mod keyboard_hook {
     const VK_SNAPSHOT: u32 = 0x2C;
     const HOTKEY_WIN_SHIFT_PRTSCR: i32 = 1;
     const HOTKEY_PRTSCR: i32 = 2;
+    const HOTKEY_CTRL_PRTSCR: i32 = 3;
+    const HOTKEY_SHIFT_PRTSCR: i32 = 4;
 
     unsafe extern "system" fn wnd_proc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
         if msg == WM_HOTKEY {
@@ -58,6 +85,16 @@ mod keyboard_hook {
                 if let Some(app) = APP_HANDLE.get() {
                     let _ = app.emit("trigger-capture", ());
                 }
+            } else if hotkey_id == HOTKEY_CTRL_PRTSCR {
+                println!("[HOTKEY] Delayed capture hotkey pressed");
+                if let Some(app) = APP_HANDLE.get() {
+                    let _ = app.emit("trigger-delayed-capture", ());
+                }
+            } else if hotkey_id == HOTKEY_SHIFT_PRTSCR {
+                println!("[HOTKEY] Repeat capture hotkey pressed");
+                if let Some(app) = APP_HANDLE.get() {
+                    let _ = app.emit("trigger-repeat-capture", ());
+                }
             }
             return LRESULT(0);
         }
@@ -102,6 +139,16 @@ mod keyboard_hook {
                     println!("[HOTKEY] PrintScreen registered");
                 }
 
+                // Ctrl+PrintScreen (delayed capture, for hover menus and tooltips)
+                if RegisterHotKey(hwnd, HOTKEY_CTRL_PRTSCR, MOD_CONTROL | MOD_NOREPEAT, VK_SNAPSHOT).is_ok() {
+                    println!("[HOTKEY] Ctrl+PrintScreen registered");
+                }
+
+                // Shift+PrintScreen (repeat last region, no UI)
+                if RegisterHotKey(hwnd, HOTKEY_SHIFT_PRTSCR, MOD_SHIFT | MOD_NOREPEAT, VK_SNAPSHOT).is_ok() {
+                    println!("[HOTKEY] Shift+PrintScreen registered");
+                }
+
                 let mut msg = MSG::default();
                 while GetMessageW(&mut msg, None, 0, 0).0 > 0 {
                     let _ = TranslateMessage(&msg);
@@ -110,6 +157,8 @@ mod keyboard_hook {
 
                 let _ = UnregisterHotKey(hwnd, HOTKEY_WIN_SHIFT_PRTSCR);
                 let _ = UnregisterHotKey(hwnd, HOTKEY_PRTSCR);
+                let _ = UnregisterHotKey(hwnd, HOTKEY_CTRL_PRTSCR);
+                let _ = UnregisterHotKey(hwnd, HOTKEY_SHIFT_PRTSCR);
                 let _ = DestroyWindow(hwnd);
             }
         });
@@ -126,25 +175,70 @@ mod keyboard_hook {
     }
 }
 
+// System tray
+const TRAY_ID: &str = "main";
+const PRESET_MENU_PREFIX: &str = "preset:";
+const TRAY_TOOLTIP: &str = "Smart PrtScr - Ready";
+// Time left to the compositor to repaint what was under our hidden windows
+const HIDE_REPAINT_DELAY_MS: u64 = 150;
+
 // State structures
 #[derive(Clone)]
 pub struct RawScreenshot {
     pub data: Vec<u8>,  // RGBA raw data
-    pub width: u32,
+    pub width: u32,     // Physical pixels
     pub height: u32,
+    pub scale_factor: f32, // Scale factor of the captured display (physical / logical pixels)
+    pub cursor: Option<cursor::CursorSprite>, // Mouse cursor at capture time, not drawn in `data`
 }
 
-#[derive(Default)]
 pub struct AppState {
+    pub capture_source: Box<dyn CaptureSource>, // Wrapped in the privacy zone filter
+    pub privacy_zones: privacy::SharedZones,
     pub current_screenshot: Mutex<Option<RawScreenshot>>,
-    pub current_screenshot_path: Mutex<Option<String>>, // Temp file path for quick loading via asset protocol
+    pub current_display: Mutex<Option<u32>>, // Display of the frozen frame
+    pub snap_targets: Mutex<Vec<window_list::SnapTarget>>, // Windows visible on the frozen frame
+    pub snap_cache: Mutex<Option<edge_snap::SnapCache>>, // Rectangles detected in the frozen frame
+    pub capture_session: Mutex<Option<String>>, // Unguessable token identifying the frozen frame URL
     pub pending_screenshot: Mutex<Option<PendingScreenshot>>,
+    pub delayed_capture: Mutex<Option<delayed_capture::Countdown>>, // Running countdown
+    pub timelapse: background::SessionSlot<timelapse::TimelapseConfig>,
+    pub watcher: background::SessionSlot<watcher::WatchConfig>,
+    pub scroll_capture: Mutex<Option<scroll_capture::ScrollSession>>,
+    pub capture_warning: Mutex<Option<blank_detect::BlankReport>>, // Frozen frame found blank
+    pub capture_method: Mutex<capture::CaptureMethod>, // Method of the next interactive capture
+    pub next_step: Mutex<u32>, // Number of the next step badge, continued across captures
+}
+
+impl Default for AppState {
+    fn default() -> Self {
+        let privacy_zones = privacy::SharedZones::default();
+        Self {
+            capture_source: Box::new(privacy::PrivacyFilter::new(capture::default_source(), privacy_zones.clone())),
+            privacy_zones,
+            current_screenshot: Mutex::new(None),
+            current_display: Mutex::new(None),
+            snap_targets: Mutex::new(Vec::new()),
+            snap_cache: Mutex::new(None),
+            capture_session: Mutex::new(None),
+            pending_screenshot: Mutex::new(None),
+            delayed_capture: Mutex::new(None),
+            timelapse: Mutex::new(None),
+            watcher: Mutex::new(None),
+            scroll_capture: Mutex::new(None),
+            capture_warning: Mutex::new(None),
+            capture_method: Mutex::new(capture::CaptureMethod::Default),
+            next_step: Mutex::new(annotations::FIRST_STEP),
+        }
+    }
 }
 
 #[derive(Clone)]
 pub struct PendingScreenshot {
     pub image_data: Vec<u8>,
     pub default_filename: String,
+    pub scale_factor: f32, // Physical / logical pixels of the image (annotations, redactions)
+    pub context: Option<context_capture::ContextFrame>, // Frame the selection is pasted into
 }
 
 // Serializable structures
@@ -163,6 +257,10 @@ pub struct TimestampOptions {
     pub bold: bool,
     pub italic: bool,
     pub underline: bool,
+    #[serde(rename = "qrCode", default)]
+    pub qr_code: bool, // Banner modes: QR code of the capture metadata next to the text
+    #[serde(default)]
+    pub caption: Option<String>, // Encoded in the QR code
 }
 
 impl Default for TimestampOptions {
@@ -177,11 +275,14 @@ impl Default for TimestampOptions {
             bold: false,
             italic: false,
             underline: false,
+            qr_code: false,
+            caption: None,
         }
     }
 }
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+// Logical (CSS) pixels relative to the captured display, see selection.rs
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq)]
 pub struct SelectionBounds {
     pub x: i32,
     pub y: i32,
@@ -196,6 +297,12 @@ pub struct SaveData {
     pub timestamp_options: TimestampOptions,
     #[serde(rename = "imageFormat")]
     pub image_format: String,
+    #[serde(rename = "outputFolder", default)]
+    pub output_folder: Option<String>, // None = save path (set by a region preset)
+    #[serde(default)]
+    pub annotations: Vec<annotations::Annotation>,
+    #[serde(default)]
+    pub redactions: Vec<redaction::Redaction>,
 }
 
 // Event payload for clipboard copy failure notification
@@ -218,6 +325,8 @@ pub struct AllSettings {
     pub timestamp_options: TimestampOptions,
     #[serde(rename = "clipboardCopyEnabled")]
     pub clipboard_copy_enabled: bool,
+    #[serde(rename = "captureDelay")]
+    pub capture_delay: u32,
 }
 
 // Get default screenshot path
@@ -258,11 +367,11 @@ fn get_banner_color(display_type: &str) -> Rgba<u8> {
 }
 
 // Result of timestamp processing: encoded bytes for file + RGBA for clipboard
-struct ProcessedImage {
-    encoded_bytes: Vec<u8>,
-    rgba_pixels: Vec<u8>,
-    width: u32,
-    height: u32,
+pub struct ProcessedImage {
+    pub encoded_bytes: Vec<u8>,
+    pub rgba_pixels: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
 }
 
 // Add timestamp to image - returns both encoded format and raw RGBA
@@ -272,7 +381,15 @@ fn add_timestamp_to_image(
     format: &str,
 ) -> Result<ProcessedImage, String> {
     let img = image::load_from_memory(image_data).map_err(|e| e.to_string())?;
-    let mut rgba_img = img.to_rgba8();
+    add_timestamp_to_rgba(img.to_rgba8(), options, format)
+}
+
+// Same as add_timestamp_to_image for already decoded pixels (background capture modes)
+fn add_timestamp_to_rgba(
+    mut rgba_img: RgbaImage,
+    options: &TimestampOptions,
+    format: &str,
+) -> Result<ProcessedImage, String> {
     let (width, height) = rgba_img.dimensions();
 
     if !options.enabled {
@@ -281,13 +398,13 @@ fn add_timestamp_to_image(
         return Ok(ProcessedImage { encoded_bytes, rgba_pixels, width, height });
     }
 
-    let timestamp = Local::now().format("%d/%m/%Y %H:%M:%S").to_string();
+    let now = Local::now();
+    let timestamp = now.format("%d/%m/%Y %H:%M:%S").to_string();
     let banner_height = (options.font_size + 20).max(30);
     let text_color = get_text_color(&options.text_color);
 
     // Load embedded font using ab_glyph
-    let font_data = include_bytes!("../fonts/arial.ttf");
-    let font = FontArc::try_from_slice(font_data).map_err(|e| e.to_string())?;
+    let font = load_font()?;
     let scale = PxScale::from(options.font_size as f32);
 
     let is_overlay = options.display_type == "overlay";
@@ -312,7 +429,18 @@ fn add_timestamp_to_image(
         let encoded_bytes = encode_image(&DynamicImage::ImageRgba8(rgba_img), format)?;
         Ok(ProcessedImage { encoded_bytes, rgba_pixels, width, height })
     } else {
-        // Banner mode: add banner above or below image
+        // Banner mode: add banner above or below image, tall enough for the QR code
+        let qr = if options.qr_code {
+            let payload = banner_qr::payload(&rgba_img, &now, options.caption.as_deref());
+            let qr = banner_qr::BannerQr::new(&payload, options.font_size, width.saturating_sub(2 * banner_qr::MARGIN))?;
+            if qr.is_none() {
+                println!("[LOG] {} Image too narrow for the banner QR code", Local::now().format("%H:%M:%S%.3f"));
+            }
+            qr
+        } else {
+            None
+        };
+        let banner_height = qr.as_ref().map_or(banner_height, |qr| banner_height.max(qr.side() + 2 * banner_qr::MARGIN));
         let new_height = height + banner_height;
         let mut new_img = RgbaImage::new(width, new_height);
 
@@ -337,13 +465,29 @@ fn add_timestamp_to_image(
             }
         }
 
+        // QR code on the side opposite to the text (left for right-aligned text), the text
+        // is aligned in the rest of the banner
+        let (area_x, area_width) = match &qr {
+            Some(qr) => {
+                let reserved = qr.side() + 2 * banner_qr::MARGIN;
+                let (qr_x, area_x) = if options.text_align == "right" {
+                    (banner_qr::MARGIN, reserved as i32)
+                } else {
+                    (width - banner_qr::MARGIN - qr.side(), 0)
+                };
+                qr.draw(&mut new_img, qr_x, banner_y + (banner_height - qr.side()) / 2);
+                (area_x, width as i32 - reserved as i32)
+            }
+            None => (0, width as i32),
+        };
+
         // Calculate text position
         let text_width = get_text_width(&font, scale, &timestamp);
         let padding = 20;
-        let text_x = match options.text_align.as_str() {
+        let text_x = area_x + match options.text_align.as_str() {
             "left" => padding,
-            "right" => width as i32 - text_width - padding,
-            _ => (width as i32 - text_width) / 2,
+            "right" => area_width - text_width - padding,
+            _ => (area_width - text_width) / 2,
         };
         let text_y = banner_y as i32 + (banner_height as i32 - options.font_size as i32) / 2;
 
@@ -354,6 +498,12 @@ fn add_timestamp_to_image(
     }
 }
 
+// Embedded font (timestamp and annotation text)
+fn load_font() -> Result<FontArc, String> {
+    let font_data = include_bytes!("../fonts/arial.ttf");
+    FontArc::try_from_slice(font_data).map_err(|e| e.to_string())
+}
+
 fn get_text_width(font: &FontArc, scale: PxScale, text: &str) -> i32 {
     let mut width = 0.0;
     for c in text.chars() {
@@ -375,6 +525,106 @@ fn encode_image(img: &DynamicImage, format: &str) -> Result<Vec<u8>, String> {
     Ok(buffer.into_inner())
 }
 
+// Crop the frozen screen to the selection and encode it as PNG (pending screenshot data)
+pub fn crop_selection(raw: &RawScreenshot, bounds: &SelectionBounds) -> Result<Vec<u8>, String> {
+    // Map logical bounds to physical pixels inside the frame
+    let rect = selection::to_physical_rect(bounds, raw.scale_factor, raw.width, raw.height)
+        .map_err(|e| e.to_string())?;
+    crop_rect(raw, &rect)
+}
+
+// Crop a rectangle already in physical pixels of the frame
+pub fn crop_rect(raw: &RawScreenshot, rect: &selection::PixelRect) -> Result<Vec<u8>, String> {
+    // Create image from raw RGBA data
+    let img = RgbaImage::from_raw(raw.width, raw.height, raw.data.clone())
+        .ok_or("Failed to create image from raw data")?;
+    let dynamic_img = DynamicImage::ImageRgba8(img);
+
+    // Crop the image
+    let cropped = dynamic_img.crop_imm(rect.x, rect.y, rect.width, rect.height);
+
+    // Convert to PNG bytes for final storage (quality matters here)
+    let mut buffer = Cursor::new(Vec::new());
+    cropped.write_to(&mut buffer, ImageFormat::Png).map_err(|e| e.to_string())?;
+    Ok(buffer.into_inner())
+}
+
+// Crop a selection out of the frozen frame (PNG) and prepare its context frame, with the
+// cursor composited when asked. A snapped window is cropped with its physical bounds
+// (`snapped`): its logical bounds are rounded and scaling them back can be off by one pixel
+// at fractional scale factors.
+pub fn prepare_selection(
+    raw: &RawScreenshot,
+    bounds: &SelectionBounds,
+    snapped: Option<selection::PixelRect>,
+    cursor: cursor::CursorOptions,
+    context: context_capture::ContextOptions,
+) -> Result<(Vec<u8>, Option<context_capture::ContextFrame>), String> {
+    let rect = match snapped {
+        Some(rect) => rect,
+        None => selection::to_physical_rect(bounds, raw.scale_factor, raw.width, raw.height).map_err(|e| e.to_string())?,
+    };
+    if cursor.include && raw.cursor.is_some() {
+        // Composite on a copy: the frozen frame stays clean for another selection
+        let mut frame = raw.clone();
+        cursor::composite(&mut frame, cursor.halo);
+        Ok((crop_rect(&frame, &rect)?, context_capture::prepare(&frame, &rect, context)?))
+    } else {
+        Ok((crop_rect(raw, &rect)?, context_capture::prepare(raw, &rect, context)?))
+    }
+}
+
+// Decode the cropped image, redact its regions, draw its annotations and paste it into its
+// context frame (None when there is nothing to apply). Redactions come first and overwrite
+// the pixels before encoding.
+fn apply_markup(
+    image_data: &[u8],
+    redactions: &[redaction::Redaction],
+    annotations: &[annotations::Annotation],
+    scale_factor: f32,
+    context: Option<&context_capture::ContextFrame>,
+) -> Result<Option<RgbaImage>, String> {
+    if redactions.is_empty() && annotations.is_empty() && context.is_none() {
+        return Ok(None);
+    }
+    let mut img = image::load_from_memory(image_data).map_err(|e| e.to_string())?.to_rgba8();
+    redaction::apply(&mut img, redactions, scale_factor)?;
+    annotations::render(&mut img, annotations, scale_factor)?;
+    Ok(Some(match context {
+        Some(context) => context.compose(&img),
+        None => img,
+    }))
+}
+
+// Apply the timestamp and write the image into `save_dir` (created if needed)
+pub fn write_screenshot(
+    image_data: &[u8],
+    scale_factor: f32,
+    context: Option<&context_capture::ContextFrame>,
+    save_dir: &Path,
+    data: &SaveData,
+) -> Result<(PathBuf, ProcessedImage), String> {
+    // Create directory if needed
+    if !save_dir.exists() {
+        fs::create_dir_all(save_dir).map_err(|e| e.to_string())?;
+    }
+
+    // Get timestamp options and format
+    let extension = if data.image_format == "png" { "png" } else { "jpg" };
+    let filename = format!("{}.{}", data.filename, extension);
+    let full_path = save_dir.join(&filename);
+
+    // Redact, draw annotations, then apply timestamp
+    let processed = match apply_markup(image_data, &data.redactions, &data.annotations, scale_factor, context)? {
+        Some(marked) => add_timestamp_to_rgba(marked, &data.timestamp_options, &data.image_format)?,
+        None => add_timestamp_to_image(image_data, &data.timestamp_options, &data.image_format)?,
+    };
+
+    // Save file
+    fs::write(&full_path, &processed.encoded_bytes).map_err(|e| e.to_string())?;
+    Ok((full_path, processed))
+}
+
 // Copy image to system clipboard from raw RGBA data
 fn copy_rgba_to_clipboard(pixels: Vec<u8>, width: u32, height: u32) -> Result<(), String> {
     let mut clipboard = Clipboard::new().map_err(|e| e.to_string())?;
@@ -430,6 +680,9 @@ async fn get_all_settings(app: AppHandle) -> Result<AllSettings, String> {
         .and_then(|v| v.as_bool())
         .unwrap_or(true);
 
+    // Delayed capture countdown (seconds)
+    let capture_delay = delayed_capture::get_stored_delay(&app);
+
     Ok(AllSettings {
         save_path,
         auto_start,
@@ -437,12 +690,18 @@ async fn get_all_settings(app: AppHandle) -> Result<AllSettings, String> {
         windows_prtscr_disabled,
         timestamp_options,
         clipboard_copy_enabled,
+        capture_delay,
     })
 }
 
 #[tauri::command]
 async fn get_save_path(app: AppHandle) -> Result<String, String> {
     println!("[LOG] {} get_save_path called", Local::now().format("%H:%M:%S%.3f"));
+    read_save_path(&app)
+}
+
+// Stored save path (sync, for background capture modes)
+fn read_save_path(app: &AppHandle) -> Result<String, String> {
     let store = app.store("settings.json").map_err(|e| e.to_string())?;
     let default_path = get_default_screenshot_path();
 
@@ -496,6 +755,11 @@ async fn reset_save_path(app: AppHandle) -> Result<String, String> {
 
 #[tauri::command]
 async fn get_timestamp_options(app: AppHandle) -> Result<TimestampOptions, String> {
+    read_timestamp_options(&app)
+}
+
+// Stored timestamp options (sync, for background capture modes)
+fn read_timestamp_options(app: &AppHandle) -> Result<TimestampOptions, String> {
     let store = app.store("settings.json").map_err(|e| e.to_string())?;
 
     if let Some(options) = store.get("timestampOptions") {
@@ -526,6 +790,11 @@ async fn reset_timestamp_options(app: AppHandle) -> Result<TimestampOptions, Str
 
 #[tauri::command]
 async fn get_image_format(app: AppHandle) -> Result<String, String> {
+    read_image_format(&app)
+}
+
+// Stored image format (sync, for background capture modes)
+fn read_image_format(app: &AppHandle) -> Result<String, String> {
     let store = app.store("settings.json").map_err(|e| e.to_string())?;
 
     if let Some(format) = store.get("imageFormat") {
@@ -537,6 +806,23 @@ async fn get_image_format(app: AppHandle) -> Result<String, String> {
     Ok("jpg".to_string())
 }
 
+// Per-capture overrides of the stamp options and format, falling back to the stored ones
+fn resolve_output_options(
+    app: &AppHandle,
+    timestamp_options: &Option<TimestampOptions>,
+    image_format: &Option<String>,
+) -> Result<(TimestampOptions, String), String> {
+    let timestamp_options = match timestamp_options {
+        Some(options) => options.clone(),
+        None => read_timestamp_options(app)?,
+    };
+    let image_format = match image_format {
+        Some(format) => format.clone(),
+        None => read_image_format(app)?,
+    };
+    Ok((timestamp_options, image_format))
+}
+
 #[tauri::command]
 async fn set_image_format(app: AppHandle, format: String) -> Result<String, String> {
     let store = app.store("settings.json").map_err(|e| e.to_string())?;
@@ -622,50 +908,113 @@ async fn set_windows_prtscr_disabled(_disabled: bool) -> Result<bool, String> {
     Ok(false)
 }
 
+// URI scheme serving the frozen screen from memory to the selection window
+const FROZEN_FRAME_SCHEME: &str = "frozen";
+
+// URL of the frozen frame for a capture session (custom protocols use http://<scheme>.localhost on Windows)
+fn frozen_frame_url(token: &str) -> String {
+    if cfg!(target_os = "windows") {
+        format!("http://{}.localhost/{}", FROZEN_FRAME_SCHEME, token)
+    } else {
+        format!("{}://localhost/{}", FROZEN_FRAME_SCHEME, token)
+    }
+}
+
+// Handler for the frozen frame protocol: serves the current screenshot as BMP
+// if the request comes from the selection window with the current session token
+fn frozen_frame_response(
+    app: &AppHandle,
+    webview_label: &str,
+    request: &tauri::http::Request<Vec<u8>>,
+) -> tauri::http::Response<Vec<u8>> {
+    let not_found = || {
+        tauri::http::Response::builder()
+            .status(404)
+            .body(Vec::new())
+            .unwrap()
+    };
+
+    if webview_label != "selection" {
+        return not_found();
+    }
+
+    let state: State<'_, AppState> = app.state();
+    let token = request.uri().path().trim_start_matches('/');
+    let session_matches = state.capture_session.lock().unwrap()
+        .as_deref()
+        .is_some_and(|session| session == token);
+    if !session_matches {
+        println!("[LOG] {} Frozen frame requested with unknown session", Local::now().format("%H:%M:%S%.3f"));
+        return not_found();
+    }
+
+    // BMP is the fastest format to encode and decode (no compression)
+    let bmp = {
+        let current = state.current_screenshot.lock().unwrap();
+        let Some(raw) = current.as_ref() else {
+            return not_found();
+        };
+        let Some(img) = RgbaImage::from_raw(raw.width, raw.height, raw.data.clone()) else {
+            return not_found();
+        };
+        let mut buffer = Cursor::new(Vec::new());
+        if DynamicImage::ImageRgba8(img).write_to(&mut buffer, ImageFormat::Bmp).is_err() {
+            return not_found();
+        }
+        buffer.into_inner()
+    };
+
+    tauri::http::Response::builder()
+        .status(200)
+        .header("Content-Type", "image/bmp")
+        .header("Cache-Control", "no-store")
+        .body(bmp)
+        .unwrap()
+}
+
 #[tauri::command]
 async fn capture_screen(_app: AppHandle, state: State<'_, AppState>) -> Result<String, String> {
     println!("[PERF] {} capture_screen command called", Local::now().format("%H:%M:%S%.3f"));
-    // Return the temp file path for asset protocol loading
-    let path_cache = state.current_screenshot_path.lock().unwrap();
-    let path = path_cache.as_ref().ok_or("No screenshot available. Capture was not performed before opening selection window.")?;
-    println!("[PERF] {} Returning path: {}", Local::now().format("%H:%M:%S%.3f"), path);
-    Ok(path.clone())
+    // Return the frozen frame URL of the current capture session
+    let session = state.capture_session.lock().unwrap();
+    let token = session.as_ref().ok_or("No screenshot available. Capture was not performed before opening selection window.")?;
+    Ok(frozen_frame_url(token))
 }
 
 #[tauri::command]
 async fn process_selection(
-    _app: AppHandle,
+    app: AppHandle,
     state: State<'_, AppState>,
     bounds: SelectionBounds,
+    cursor: Option<cursor::CursorOptions>,
+    context: Option<context_capture::ContextOptions>,
 ) -> Result<(), String> {
     println!("[LOG] {} process_selection called with bounds: {:?}", Local::now().format("%H:%M:%S%.3f"), bounds);
+    let cursor = cursor.unwrap_or_else(|| cursor::read_cursor_options(&app));
+    let context = context.unwrap_or_else(|| context_capture::read_context_options(&app));
     let current = state.current_screenshot.lock().unwrap();
     let raw = current.as_ref().ok_or("No screenshot available")?;
-
-    // Create image from raw RGBA data
-    let img = RgbaImage::from_raw(raw.width, raw.height, raw.data.clone())
-        .ok_or("Failed to create image from raw data")?;
-    let dynamic_img = DynamicImage::ImageRgba8(img);
-
-    // Crop the image
-    let cropped = dynamic_img.crop_imm(
-        bounds.x as u32,
-        bounds.y as u32,
-        bounds.width as u32,
-        bounds.height as u32,
-    );
-
-    // Convert to PNG bytes for final storage (quality matters here)
-    let mut buffer = Cursor::new(Vec::new());
-    cropped.write_to(&mut buffer, ImageFormat::Png).map_err(|e| e.to_string())?;
-    let cropped_data = buffer.into_inner();
+    let scale_factor = raw.scale_factor;
+    let snapped = state.snap_targets.lock().unwrap().iter().find(|t| t.bounds == bounds).map(|t| t.rect);
+    let (cropped_data, context_frame) = prepare_selection(raw, &bounds, snapped, cursor, context)?;
 
     // Store pending screenshot
     drop(current); // Release lock before acquiring another
+
+    // Remember the region for "capture last region"
+    let display_id = *state.current_display.lock().unwrap();
+    if let Some(display_id) = display_id {
+        if let Err(e) = repeat_capture::remember_region(&app, display_id, &bounds) {
+            println!("[LOG] {} Cannot remember last region: {}", Local::now().format("%H:%M:%S%.3f"), e);
+        }
+    }
+
     let mut pending = state.pending_screenshot.lock().unwrap();
     *pending = Some(PendingScreenshot {
         image_data: cropped_data,
         default_filename: generate_default_filename(),
+        scale_factor,
+        context: context_frame,
     });
     drop(pending); // Release lock
 
@@ -689,29 +1038,21 @@ async fn save_screenshot(
 
     let screenshot = pending.ok_or("No pending screenshot")?;
 
-    // Get save path
-    let save_path = get_save_path(app.clone()).await?;
-    let save_dir = PathBuf::from(&save_path);
-
-    // Create directory if needed
-    if !save_dir.exists() {
-        fs::create_dir_all(&save_dir).map_err(|e| e.to_string())?;
-    }
-
-    // Get timestamp options and format
-    let extension = if data.image_format == "png" { "png" } else { "jpg" };
-    let filename = format!("{}.{}", data.filename, extension);
-    let full_path = save_dir.join(&filename);
+    // Get save path (a region preset may carry its own folder)
+    let save_dir = match &data.output_folder {
+        Some(folder) if !folder.is_empty() => PathBuf::from(folder),
+        _ => PathBuf::from(get_save_path(app.clone()).await?),
+    };
 
-    // Apply timestamp
-    let processed = add_timestamp_to_image(
+    // Apply timestamp and save file
+    let (full_path, processed) = write_screenshot(
         &screenshot.image_data,
-        &data.timestamp_options,
-        &data.image_format,
+        screenshot.scale_factor,
+        screenshot.context.as_ref(),
+        &save_dir,
+        &data,
     )?;
-
-    // Save file
-    fs::write(&full_path, &processed.encoded_bytes).map_err(|e| e.to_string())?;
+    annotations::advance_steps(&state, &data.annotations);
 
     // Copy to clipboard if enabled (after successful file save per FR-006)
     let store = app.store("settings.json").map_err(|e| e.to_string())?;
@@ -735,6 +1076,7 @@ async fn save_screenshot(
     // Save settings
     set_timestamp_options(app.clone(), data.timestamp_options).await?;
     set_image_format(app.clone(), data.image_format).await?;
+    repeat_capture::remember_action(&app, repeat_capture::LastAction::Save)?;
 
     // Show file in explorer
     #[cfg(target_os = "windows")]
@@ -754,21 +1096,29 @@ async fn copy_to_clipboard_only(
     state: State<'_, AppState>,
     timestamp_options: TimestampOptions,
     image_format: String,
+    annotations: Option<Vec<annotations::Annotation>>,
+    redactions: Option<Vec<redaction::Redaction>>,
 ) -> Result<(), String> {
     println!("[LOG] {} copy_to_clipboard_only called", Local::now().format("%H:%M:%S%.3f"));
 
     // Get pending screenshot (don't take ownership yet in case of failure)
-    let image_data = {
+    let (image_data, scale_factor, context) = {
         let pending = state.pending_screenshot.lock().unwrap();
         let screenshot = pending.as_ref().ok_or("No pending screenshot")?;
-        screenshot.image_data.clone()
+        (screenshot.image_data.clone(), screenshot.scale_factor, screenshot.context.clone())
     };
 
-    // Apply timestamp if enabled (FR-009)
-    let processed = add_timestamp_to_image(&image_data, &timestamp_options, &image_format)?;
+    // Redact, draw annotations, then apply timestamp if enabled (FR-009)
+    let annotations = annotations.unwrap_or_default();
+    let redactions = redactions.unwrap_or_default();
+    let processed = match apply_markup(&image_data, &redactions, &annotations, scale_factor, context.as_ref())? {
+        Some(marked) => add_timestamp_to_rgba(marked, &timestamp_options, &image_format)?,
+        None => add_timestamp_to_image(&image_data, &timestamp_options, &image_format)?,
+    };
 
     // Copy to clipboard - use raw RGBA directly
     copy_rgba_to_clipboard(processed.rgba_pixels, processed.width, processed.height)?;
+    annotations::advance_steps(&state, &annotations);
 
     // Success: clear pending screenshot
     {
@@ -778,12 +1128,31 @@ async fn copy_to_clipboard_only(
 
     // Save timestamp options for next time
     set_timestamp_options(app.clone(), timestamp_options).await?;
-    set_image_format(app, image_format).await?;
+    set_image_format(app.clone(), image_format).await?;
+    repeat_capture::remember_action(&app, repeat_capture::LastAction::Copy)?;
 
     println!("[LOG] {} Screenshot copied to clipboard (no file saved)", Local::now().format("%H:%M:%S%.3f"));
     Ok(())
 }
 
+// Windows of the frozen frame, topmost first (selection window snapping)
+#[tauri::command]
+async fn get_snap_targets(state: State<'_, AppState>) -> Result<Vec<window_list::SnapTarget>, String> {
+    Ok(state.snap_targets.lock().unwrap().clone())
+}
+
+// Visible top-level windows, screen coordinates
+#[tauri::command]
+async fn list_windows() -> Result<Vec<window_list::WindowInfo>, String> {
+    window_list::list_windows()
+}
+
+// Display of the frozen frame, to offer the region presets of that display
+#[tauri::command]
+async fn get_capture_display(state: State<'_, AppState>) -> Result<Option<u32>, String> {
+    Ok(*state.current_display.lock().unwrap())
+}
+
 #[tauri::command]
 async fn get_default_filename(state: State<'_, AppState>) -> Result<String, String> {
     println!("[LOG] {} get_default_filename called", Local::now().format("%H:%M:%S%.3f"));
@@ -806,17 +1175,29 @@ async fn get_preview_image(state: State<'_, AppState>) -> Result<String, String>
 }
 
 #[tauri::command]
-async fn cancel_screenshot(state: State<'_, AppState>) -> Result<(), String> {
+async fn cancel_screenshot(app: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
     println!("[LOG] {} cancel_screenshot called", Local::now().format("%H:%M:%S%.3f"));
+    reset_capture_state(&app, &state);
+    Ok(())
+}
+
+// Forget everything about the current capture (frozen frame, selection data, pending image)
+fn reset_capture_state(app: &AppHandle, state: &AppState) {
+    scroll_capture::abort(app, state);
     let mut pending = state.pending_screenshot.lock().unwrap();
     *pending = None;
     drop(pending);
     let mut current = state.current_screenshot.lock().unwrap();
     *current = None;
     drop(current);
-    let mut path_cache = state.current_screenshot_path.lock().unwrap();
-    *path_cache = None;
-    Ok(())
+    let mut display = state.current_display.lock().unwrap();
+    *display = None;
+    drop(display);
+    state.snap_targets.lock().unwrap().clear();
+    *state.snap_cache.lock().unwrap() = None;
+    *state.capture_warning.lock().unwrap() = None;
+    let mut session = state.capture_session.lock().unwrap();
+    *session = None;
 }
 
 fn open_filename_dialog(app: &AppHandle) -> Result<(), String> {
@@ -843,6 +1224,47 @@ fn open_filename_dialog(app: &AppHandle) -> Result<(), String> {
     Ok(())
 }
 
+// Hide our visible windows (settings, dialog, countdown) so they are not in the frozen frame.
+// Waits for the compositor to repaint, so only call it off the event loop and async workers
+// (see spawn_selection_window).
+fn hide_own_windows(app: &AppHandle) -> Vec<tauri::WebviewWindow> {
+    let mut hidden = Vec::new();
+    for (label, window) in app.webview_windows() {
+        if !window.is_visible().unwrap_or(false) {
+            continue;
+        }
+        match window.hide() {
+            Ok(()) => hidden.push(window),
+            Err(e) => println!("[LOG] {} Cannot hide window {}: {}", Local::now().format("%H:%M:%S%.3f"), label, e),
+        }
+    }
+    if !hidden.is_empty() {
+        std::thread::sleep(std::time::Duration::from_millis(HIDE_REPAINT_DELAY_MS));
+    }
+    hidden
+}
+
+fn restore_windows(windows: Vec<tauri::WebviewWindow>) {
+    for window in windows {
+        if let Err(e) = window.show() {
+            println!("[LOG] {} Cannot restore window {}: {}", Local::now().format("%H:%M:%S%.3f"), window.label(), e);
+        }
+    }
+}
+
+// Run open_selection_window on a blocking task, for callers on the event loop or an async worker
+fn spawn_selection_window(app: &AppHandle) -> tauri::async_runtime::JoinHandle<Result<(), String>> {
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let state: State<'_, AppState> = app.state();
+        let result = open_selection_window(&app, &state);
+        if let Err(e) = &result {
+            println!("[LOG] {} Capture failed: {}", Local::now().format("%H:%M:%S%.3f"), e);
+        }
+        result
+    })
+}
+
 fn open_selection_window(app: &AppHandle, state: &State<'_, AppState>) -> Result<(), String> {
     use std::time::Instant;
     let start = Instant::now();
@@ -855,46 +1277,60 @@ fn open_selection_window(app: &AppHandle, state: &State<'_, AppState>) -> Result
 
     // Capture screen BEFORE opening the selection window
     let t1 = Instant::now();
-    let screens = Screen::all().map_err(|e| e.to_string())?;
-    let screen = screens.first().ok_or("No screen found")?;
-    println!("[PERF] {} Screen::all() took {:?}", Local::now().format("%H:%M:%S%.3f"), t1.elapsed());
+    let displays = state.capture_source.displays()?;
+    let info = displays.first().ok_or("No screen found")?.clone();
+    println!("[PERF] {} displays() took {:?}", Local::now().format("%H:%M:%S%.3f"), t1.elapsed());
+
+    // Keep our own windows out of the frame
+    let t4 = Instant::now();
+    let hidden = hide_own_windows(app);
+    println!("[PERF] {} hide_own_windows() took {:?} ({} windows)", Local::now().format("%H:%M:%S%.3f"), t4.elapsed(), hidden.len());
 
+    // Method forced by a retry after a blank capture, for this capture only
+    let method = std::mem::take(&mut *state.capture_method.lock().unwrap());
     let t2 = Instant::now();
-    let capture = screen.capture().map_err(|e| e.to_string())?;
-    println!("[PERF] {} screen.capture() took {:?}", Local::now().format("%H:%M:%S%.3f"), t2.elapsed());
+    let capture = capture::capture_display_with(state.capture_source.as_ref(), &info, method);
+    println!("[PERF] {} capture_display() took {:?}", Local::now().format("%H:%M:%S%.3f"), t2.elapsed());
 
-    // Get screen info for window size
-    let info = screen.display_info;
+    // Restore them even when the capture failed
+    let t5 = Instant::now();
+    restore_windows(hidden);
+    println!("[PERF] {} restore_windows() took {:?}", Local::now().format("%H:%M:%S%.3f"), t5.elapsed());
+    let capture = capture?;
 
-    // Store raw RGBA data
+    // Enumerate windows while the selection window does not exist yet (snapping targets)
     let t3 = Instant::now();
-    let rgba_data = capture.as_raw().to_vec();
-    let width = capture.width();
-    let height = capture.height();
-    println!("[PERF] {} as_raw().to_vec() took {:?}", Local::now().format("%H:%M:%S%.3f"), t3.elapsed());
+    let targets = match window_list::list_windows() {
+        Ok(windows) => window_list::snap_targets(&windows, &info, capture.width, capture.height),
+        Err(e) => {
+            println!("[LOG] {} Window enumeration failed: {}", Local::now().format("%H:%M:%S%.3f"), e);
+            Vec::new()
+        }
+    };
+    println!("[PERF] {} list_windows() took {:?} ({} windows)", Local::now().format("%H:%M:%S%.3f"), t3.elapsed(), targets.len());
 
-    // Write BMP to temp file for fast loading via asset protocol
-    let t4 = Instant::now();
-    let img = RgbaImage::from_raw(width, height, rgba_data.clone())
-        .ok_or("Failed to create image from raw data")?;
-    let temp_path = std::env::temp_dir().join("smart-prtscr-preview.bmp");
-    img.save(&temp_path).map_err(|e| e.to_string())?;
-    let temp_path_str = temp_path.to_string_lossy().to_string();
-    println!("[PERF] {} BMP save to temp file took {:?}", Local::now().format("%H:%M:%S%.3f"), t4.elapsed());
+    // Warn about black frames (protected video, GPU overlays)
+    let blank = blank_detect::check_frame(state, &capture, info.id, method);
 
-    // Store the screenshot data in state
+    // Store the screenshot data in state, served to the selection window by the frozen frame protocol
     {
         let mut current = state.current_screenshot.lock().unwrap();
-        *current = Some(RawScreenshot {
-            data: rgba_data,
-            width,
-            height,
-        });
+        *current = Some(capture);
+    }
+    {
+        let mut display = state.current_display.lock().unwrap();
+        *display = Some(info.id);
+    }
+    {
+        let mut snap_targets = state.snap_targets.lock().unwrap();
+        *snap_targets = targets;
     }
     {
-        let mut path_cache = state.current_screenshot_path.lock().unwrap();
-        *path_cache = Some(temp_path_str);
+        let mut session = state.capture_session.lock().unwrap();
+        *session = Some(uuid::Uuid::new_v4().simple().to_string());
     }
+    // Detect snapping rectangles while the selection window loads
+    edge_snap::warm_up(app);
     println!("[PERF] {} Total before window creation: {:?}", Local::now().format("%H:%M:%S%.3f"), start.elapsed());
 
     let t6 = Instant::now();
@@ -916,6 +1352,9 @@ fn open_selection_window(app: &AppHandle, state: &State<'_, AppState>) -> Result
     let t7 = Instant::now();
     open_filename_dialog(app)?;
     println!("[PERF] {} filename-dialog creation took {:?}", Local::now().format("%H:%M:%S%.3f"), t7.elapsed());
+    if let Some(report) = &blank {
+        blank_detect::notify(app, report);
+    }
 
     println!("[PERF] {} TOTAL open_selection_window: {:?}", Local::now().format("%H:%M:%S%.3f"), start.elapsed());
 
@@ -949,9 +1388,9 @@ fn open_main_window(app: &AppHandle) -> Result<(), String> {
 }
 
 #[tauri::command]
-async fn start_capture(app: AppHandle, state: State<'_, AppState>) -> Result<(), String> {
+async fn start_capture(app: AppHandle) -> Result<(), String> {
     println!("[LOG] {} start_capture called", Local::now().format("%H:%M:%S%.3f"));
-    open_selection_window(&app, &state)
+    spawn_selection_window(&app).await.map_err(|e| e.to_string())?
 }
 
 #[tauri::command]
@@ -1012,6 +1451,80 @@ async fn open_folder(path: String) -> Result<(), String> {
     Ok(())
 }
 
+// Tray menu (rebuilt when the region presets change)
+fn build_tray_menu(app: &AppHandle) -> tauri::Result<Menu<tauri::Wry>> {
+    let open_item = MenuItemBuilder::with_id("open", "Ouvrir").build(app)?;
+    let capture_item = MenuItemBuilder::with_id("capture", "Capturer (PrtScr)").build(app)?;
+    let repeat_item = MenuItemBuilder::with_id("repeat", "Capturer la dernière zone (Maj+Impr)").build(app)?;
+
+    // One entry per region preset
+    let presets = presets::read_presets(app);
+    let mut presets_builder = SubmenuBuilder::new(app, "Zones enregistrées");
+    if presets.is_empty() {
+        let empty_item = MenuItemBuilder::with_id("presets-empty", "Aucune zone enregistrée")
+            .enabled(false)
+            .build(app)?;
+        presets_builder = presets_builder.item(&empty_item);
+    }
+    for preset in &presets {
+        let item = MenuItemBuilder::with_id(format!("{}{}", PRESET_MENU_PREFIX, preset.id), &preset.name).build(app)?;
+        presets_builder = presets_builder.item(&item);
+    }
+    let presets_menu = presets_builder.build()?;
+
+    let delay_3_item = MenuItemBuilder::with_id("delay-3", "Dans 3 secondes").build(app)?;
+    let delay_5_item = MenuItemBuilder::with_id("delay-5", "Dans 5 secondes").build(app)?;
+    let delay_10_item = MenuItemBuilder::with_id("delay-10", "Dans 10 secondes").build(app)?;
+    let delay_custom_item = MenuItemBuilder::with_id("delay-custom", "Délai personnalisé (Ctrl+Impr)").build(app)?;
+    let delay_cancel_item = MenuItemBuilder::with_id("delay-cancel", "Annuler la capture différée").build(app)?;
+    let delay_menu = SubmenuBuilder::new(app, "Capture différée")
+        .item(&delay_3_item)
+        .item(&delay_5_item)
+        .item(&delay_10_item)
+        .item(&delay_custom_item)
+        .separator()
+        .item(&delay_cancel_item)
+        .build()?;
+    let timelapse_start_item = MenuItemBuilder::with_id("timelapse-start", "Démarrer le time-lapse").build(app)?;
+    let timelapse_stop_item = MenuItemBuilder::with_id("timelapse-stop", "Arrêter le time-lapse").build(app)?;
+    let watch_start_item = MenuItemBuilder::with_id("watch-start", "Surveiller la zone").build(app)?;
+    let watch_stop_item = MenuItemBuilder::with_id("watch-stop", "Arrêter la surveillance").build(app)?;
+    let scroll_stop_item = MenuItemBuilder::with_id("scroll-stop", "Terminer la capture avec défilement").build(app)?;
+    let folder_item = MenuItemBuilder::with_id("folder", "Dossier de sauvegarde").build(app)?;
+    let quit_item = MenuItemBuilder::with_id("quit", "Quitter").build(app)?;
+
+    MenuBuilder::new(app)
+        .item(&open_item)
+        .item(&capture_item)
+        .item(&repeat_item)
+        .item(&presets_menu)
+        .item(&delay_menu)
+        .separator()
+        .item(&timelapse_start_item)
+        .item(&timelapse_stop_item)
+        .item(&watch_start_item)
+        .item(&watch_stop_item)
+        .item(&scroll_stop_item)
+        .separator()
+        .item(&folder_item)
+        .separator()
+        .item(&quit_item)
+        .build()
+}
+
+// Rebuild the tray menu after a change of its dynamic entries
+pub fn refresh_tray_menu(app: &AppHandle) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    match build_tray_menu(app) {
+        Ok(menu) => {
+            let _ = tray.set_menu(Some(menu));
+        }
+        Err(e) => println!("[LOG] {} Cannot rebuild tray menu: {}", Local::now().format("%H:%M:%S%.3f"), e),
+    }
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
@@ -1027,6 +1540,9 @@ pub fn run() {
             let _ = open_main_window(app);
         }))
         .manage(AppState::default())
+        .register_uri_scheme_protocol(FROZEN_FRAME_SCHEME, |ctx, request| {
+            frozen_frame_response(ctx.app_handle(), ctx.webview_label(), &request)
+        })
         .invoke_handler(tauri::generate_handler![
             get_all_settings,
             get_save_path,
@@ -1048,6 +1564,10 @@ pub fn run() {
             save_screenshot,
             copy_to_clipboard_only,
             get_default_filename,
+            get_capture_display,
+            get_snap_targets,
+            edge_snap::find_snap_rectangles,
+            list_windows,
             get_preview_image,
             cancel_screenshot,
             start_capture,
@@ -1057,39 +1577,115 @@ pub fn run() {
             log_message,
             show_selection_window,
             focus_dialog,
+            delayed_capture::start_delayed_capture,
+            delayed_capture::cancel_delayed_capture,
+            delayed_capture::get_capture_delay,
+            delayed_capture::set_capture_delay,
+            timelapse::start_timelapse,
+            timelapse::stop_timelapse,
+            timelapse::get_timelapse_status,
+            watcher::start_region_watch,
+            watcher::stop_region_watch,
+            watcher::get_region_watch_status,
+            repeat_capture::capture_last_region,
+            repeat_capture::get_last_region,
+            annotations::get_next_step,
+            annotations::reset_step_counter,
+            auto_redact::get_redaction_rules,
+            auto_redact::set_redaction_rules,
+            auto_redact::get_auto_redact_options,
+            auto_redact::set_auto_redact_options,
+            auto_redact::detect_sensitive_text,
+            barcode::decode_selection_codes,
+            barcode::copy_code_text,
+            barcode::open_code_url,
+            ocr::get_ocr_options,
+            ocr::get_ocr_languages,
+            ocr::ocr_selection,
+            presets::get_region_presets,
+            privacy::get_privacy_zones,
+            privacy::set_privacy_zones,
+            presets::set_region_presets,
+            presets::add_region_preset,
+            presets::capture_region_preset,
+            cursor::get_cursor_options,
+            cursor::set_cursor_options,
+            context_capture::get_context_options,
+            context_capture::set_context_options,
+            blank_detect::get_capture_warning,
+            blank_detect::check_selection_blank,
+            blank_detect::retry_capture,
+            scroll_capture::start_scroll_capture,
+            scroll_capture::stop_scroll_capture,
+            scheduler::get_scheduler_settings,
+            scheduler::set_scheduler_settings,
+            scheduler::preview_cron_expression,
         ])
         .setup(|app| {
+            // Privacy zones, before any capture can run
+            privacy::load(app.handle());
+
             // Setup system tray
-            let open_item = MenuItemBuilder::with_id("open", "Ouvrir").build(app)?;
-            let capture_item = MenuItemBuilder::with_id("capture", "Capturer (PrtScr)").build(app)?;
-            let folder_item = MenuItemBuilder::with_id("folder", "Dossier de sauvegarde").build(app)?;
-            let quit_item = MenuItemBuilder::with_id("quit", "Quitter").build(app)?;
-
-            let menu = MenuBuilder::new(app)
-                .item(&open_item)
-                .item(&capture_item)
-                .separator()
-                .item(&folder_item)
-                .separator()
-                .item(&quit_item)
-                .build()?;
+            let menu = build_tray_menu(app.handle())?;
 
             let icon_bytes = include_bytes!("../icons/icon.ico");
             let icon = Image::from_bytes(icon_bytes).unwrap();
 
-            let _tray = TrayIconBuilder::new()
+            let _tray = TrayIconBuilder::with_id(TRAY_ID)
                 .icon(icon)
                 .menu(&menu)
                 .show_menu_on_left_click(false)
-                .tooltip("Smart PrtScr - Ready")
+                .tooltip(TRAY_TOOLTIP)
                 .on_menu_event(move |app, event| {
                     match event.id().as_ref() {
                         "open" => {
                             let _ = open_main_window(app);
                         }
                         "capture" => {
-                            let state: State<'_, AppState> = app.state();
-                            let _ = open_selection_window(app, &state);
+                            // Off the main thread: our windows must actually disappear while it waits
+                            drop(spawn_selection_window(app));
+                        }
+                        "repeat" => {
+                            repeat_capture::run_and_notify(app);
+                        }
+                        id if id.starts_with(PRESET_MENU_PREFIX) => {
+                            presets::capture_and_notify(app, &id[PRESET_MENU_PREFIX.len()..]);
+                        }
+                        "delay-3" => {
+                            let _ = delayed_capture::start(app, 3);
+                        }
+                        "delay-5" => {
+                            let _ = delayed_capture::start(app, 5);
+                        }
+                        "delay-10" => {
+                            let _ = delayed_capture::start(app, 10);
+                        }
+                        "delay-custom" => {
+                            let _ = delayed_capture::start(app, delayed_capture::get_stored_delay(app));
+                        }
+                        "delay-cancel" => {
+                            delayed_capture::cancel(app);
+                        }
+                        "timelapse-start" => {
+                            // Without a previous configuration, open the settings to create one
+                            if timelapse::start(app, None).is_err() {
+                                let _ = open_main_window(app);
+                            }
+                        }
+                        "timelapse-stop" => {
+                            timelapse::stop(app);
+                        }
+                        "watch-start" => {
+                            // Without a previous configuration, open the settings to create one
+                            if watcher::start(app, None).is_err() {
+                                let _ = open_main_window(app);
+                            }
+                        }
+                        "watch-stop" => {
+                            watcher::stop(app);
+                        }
+                        "scroll-stop" => {
+                            scroll_capture::finish_from_tray(app);
                         }
                         "folder" => {
                             let app_clone = app.clone();
@@ -1126,14 +1722,31 @@ pub fn run() {
             let app_handle = app.handle().clone();
             app.listen("trigger-capture", move |_event| {
                 println!("[EVENT] trigger-capture received");
-                let state: State<'_, AppState> = app_handle.state();
                 if let Some(window) = app_handle.get_webview_window("selection") {
                     let _ = window.emit("capture-full-screen", ());
                 } else {
-                    let _ = open_selection_window(&app_handle, &state);
+                    drop(spawn_selection_window(&app_handle));
                 }
             });
 
+            // Event listener for delayed capture hotkey
+            let app_handle = app.handle().clone();
+            app.listen("trigger-delayed-capture", move |_event| {
+                println!("[EVENT] trigger-delayed-capture received");
+                let seconds = delayed_capture::get_stored_delay(&app_handle);
+                let _ = delayed_capture::start(&app_handle, seconds);
+            });
+
+            // Event listener for repeat capture hotkey
+            let app_handle = app.handle().clone();
+            app.listen("trigger-repeat-capture", move |_event| {
+                println!("[EVENT] trigger-repeat-capture received");
+                repeat_capture::run_and_notify(&app_handle);
+            });
+
+            // Start scheduled captures
+            scheduler::start(app.handle());
+
             // Start global hotkey handler
             #[cfg(target_os = "windows")]
             keyboard_hook::start_hook(app.handle().clone());
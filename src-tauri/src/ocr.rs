@@ -0,0 +1,309 @@
+// Offline OCR of the cropped selection: the image is piped to a Tesseract engine run as a
+// child process (PNG on stdin, TSV on stdout). The engine is looked up in the `ocr` resource folder of the
+// app (bundled with tauri.ocr.conf.json, with its `tessdata` language models), then in
+// SMART_PRTSCR_TESSERACT. There is no PATH lookup: a build without the engine reports OCR
+// as unavailable, and the dialog disables it. Nothing leaves the machine.
+
+use crate::redaction::{self, Redaction};
+use crate::{get_save_path, AppState};
+use arboard::Clipboard;
+use chrono::Local;
+use image::{imageops, ImageFormat, RgbaImage};
+use serde::{Deserialize, Serialize};
+use std::io::{Cursor, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Instant;
+use tauri::{AppHandle, Manager, State};
+use tauri_plugin_store::StoreExt;
+
+// Engine executable used when none is bundled (development, builds without OCR)
+pub const TESSERACT_ENV: &str = "SMART_PRTSCR_TESSERACT";
+// Images captured below this scale factor are enlarged: Tesseract misses small UI text
+const MIN_SCALE_FACTOR: f32 = 2.0;
+
+fn default_language() -> String {
+    "eng".to_string()
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct OcrOptions {
+    #[serde(default = "default_language")]
+    pub language: String, // Tesseract model(s), e.g. "eng" or "fra+eng"
+    #[serde(rename = "writeSidecar", default)]
+    pub write_sidecar: bool, // Also write the text next to the image (.txt)
+}
+
+impl Default for OcrOptions {
+    fn default() -> Self {
+        Self { language: default_language(), write_sidecar: false }
+    }
+}
+
+// Recognised word, physical pixels of the cropped image
+#[derive(Clone, Serialize, Debug)]
+pub struct OcrWord {
+    pub text: String,
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+    pub confidence: f32, // 0-100
+    pub line: u32,       // Index of its text line, in reading order
+}
+
+#[derive(Clone, Serialize, Debug)]
+pub struct OcrResult {
+    pub text: String,
+    pub words: Vec<OcrWord>,
+    pub language: String,
+    #[serde(rename = "sidecarPath")]
+    pub sidecar_path: Option<String>,
+}
+
+// Text file written next to the image (same name, .txt)
+#[derive(Clone, Deserialize, Debug)]
+pub struct OcrSidecar {
+    pub filename: String,
+    #[serde(rename = "outputFolder", default)]
+    pub output_folder: Option<String>, // None = save path
+}
+
+pub fn read_ocr_options(app: &AppHandle) -> OcrOptions {
+    app.store("settings.json")
+        .ok()
+        .and_then(|store| store.get("ocrOptions"))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+struct Engine {
+    program: PathBuf,
+    tessdata: Option<PathBuf>, // None = models installed with the engine
+}
+
+fn find_engine(app: &AppHandle) -> Result<Engine, String> {
+    let executable = if cfg!(target_os = "windows") { "tesseract.exe" } else { "tesseract" };
+    if let Ok(resources) = app.path().resource_dir() {
+        let bundled = resources.join("ocr");
+        if bundled.join(executable).is_file() {
+            return Ok(Engine { program: bundled.join(executable), tessdata: Some(bundled.join("tessdata")) });
+        }
+    }
+    if let Some(program) = std::env::var_os(TESSERACT_ENV) {
+        return Ok(Engine { program: PathBuf::from(program), tessdata: None });
+    }
+    Err(format!(
+        "OCR unavailable: this build does not include the OCR engine (set {} to use an installed Tesseract)",
+        TESSERACT_ENV
+    ))
+}
+
+// Run the engine with the given arguments and standard input, returns its standard output.
+// Blocks until the engine exits: call it from a blocking task.
+fn run(engine: &Engine, args: &[&str], input: Option<&[u8]>) -> Result<String, String> {
+    let mut command = Command::new(&engine.program);
+    if let Some(tessdata) = &engine.tessdata {
+        command.arg("--tessdata-dir").arg(tessdata);
+    }
+    command
+        .args(args)
+        .stdin(if input.is_some() { Stdio::piped() } else { Stdio::null() })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    #[cfg(target_os = "windows")]
+    {
+        // No console window flashing over the dialog
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+        command.creation_flags(CREATE_NO_WINDOW);
+    }
+
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("OCR engine not found ({}): {}", engine.program.display(), e))?;
+    if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+        // The engine reads the whole image before writing anything; stdin is closed on drop
+        stdin.write_all(input).map_err(|e| format!("OCR failed: {}", e))?;
+    }
+    let output = child.wait_with_output().map_err(|e| format!("OCR failed: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("OCR failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+// Installed language models (without the orientation model)
+pub async fn languages(app: &AppHandle) -> Result<Vec<String>, String> {
+    let engine = find_engine(app)?;
+    let output = tauri::async_runtime::spawn_blocking(move || run(&engine, &["--list-langs"], None))
+        .await
+        .map_err(|e| e.to_string())??;
+    Ok(output
+        .lines()
+        .skip(1) // "List of available languages in ..."
+        .map(str::trim)
+        .filter(|lang| !lang.is_empty() && *lang != "osd")
+        .map(str::to_string)
+        .collect())
+}
+
+// Words of a TSV result and the text rebuilt from them: words of a line separated by a
+// space, lines by a newline, paragraphs by an empty line
+fn parse_tsv(tsv: &str, upscale: u32) -> (String, Vec<OcrWord>) {
+    let mut text = String::new();
+    let mut words = Vec::new();
+    let mut previous: Option<(&str, &str, &str, &str)> = None;
+    let mut line_index = 0;
+
+    // level page block paragraph line word left top width height confidence text
+    for row in tsv.lines().skip(1) {
+        let fields: Vec<&str> = row.split('\t').collect();
+        if fields.len() < 12 || fields[0] != "5" || fields[11].trim().is_empty() {
+            continue;
+        }
+        let number = |i: usize| fields[i].parse::<u32>().unwrap_or(0) / upscale;
+
+        let line = (fields[1], fields[2], fields[3], fields[4]);
+        match previous {
+            Some(prev) if prev == line => text.push(' '),
+            Some(prev) if (prev.0, prev.1, prev.2) == (line.0, line.1, line.2) => text.push('\n'),
+            Some(_) => text.push_str("\n\n"),
+            None => {}
+        }
+        if previous.is_some_and(|prev| prev != line) {
+            line_index += 1;
+        }
+        previous = Some(line);
+        text.push_str(fields[11].trim());
+
+        words.push(OcrWord {
+            text: fields[11].trim().to_string(),
+            x: number(6),
+            y: number(7),
+            width: number(8),
+            height: number(9),
+            confidence: fields[10].parse().unwrap_or(0.0),
+            line: line_index,
+        });
+    }
+    (text, words)
+}
+
+// PNG piped to the engine, enlarged `upscale` times
+fn encode_input(img: &RgbaImage, upscale: u32) -> Result<Vec<u8>, String> {
+    let (width, height) = img.dimensions();
+    let mut buffer = Cursor::new(Vec::new());
+    let result = if upscale > 1 {
+        imageops::resize(img, width * upscale, height * upscale, imageops::FilterType::CatmullRom)
+            .write_to(&mut buffer, ImageFormat::Png)
+    } else {
+        img.write_to(&mut buffer, ImageFormat::Png)
+    };
+    result.map_err(|e| e.to_string())?;
+    Ok(buffer.into_inner())
+}
+
+// Recognise the text of an image; word boxes are in pixels of `img`
+pub async fn recognize(app: &AppHandle, img: &RgbaImage, scale_factor: f32, language: &str) -> Result<(String, Vec<OcrWord>), String> {
+    if language.is_empty() || !language.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '+') {
+        return Err(format!("Invalid OCR language: {}", language));
+    }
+    let started = Instant::now();
+
+    let upscale = if scale_factor < MIN_SCALE_FACTOR { 2 } else { 1 };
+    let engine = find_engine(app)?;
+    let img = img.clone();
+    let language_arg = language.to_string();
+    // Encoding and the engine run block for a while: keep them off the async workers
+    let tsv = tauri::async_runtime::spawn_blocking(move || {
+        let input = encode_input(&img, upscale)?;
+        run(&engine, &["stdin", "stdout", "-l", &language_arg, "tsv"], Some(&input))
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    let (text, words) = parse_tsv(&tsv, upscale);
+    println!(
+        "[PERF] {} OCR ({}) took {:?}: {} words",
+        Local::now().format("%H:%M:%S%.3f"),
+        language,
+        started.elapsed(),
+        words.len()
+    );
+    Ok((text, words))
+}
+
+fn write_sidecar(save_dir: &Path, filename: &str, text: &str) -> Result<PathBuf, String> {
+    if !save_dir.exists() {
+        std::fs::create_dir_all(save_dir).map_err(|e| e.to_string())?;
+    }
+    let path = save_dir.join(format!("{}.txt", filename));
+    std::fs::write(&path, text).map_err(|e| e.to_string())?;
+    Ok(path)
+}
+
+#[tauri::command]
+pub async fn get_ocr_options(app: AppHandle) -> Result<OcrOptions, String> {
+    Ok(read_ocr_options(&app))
+}
+
+#[tauri::command]
+pub async fn get_ocr_languages(app: AppHandle) -> Result<Vec<String>, String> {
+    languages(&app).await
+}
+
+// Recognise the text of the pending selection (redactions applied first) and copy it to
+// the clipboard; with a sidecar, the text is also written next to where the image goes
+#[tauri::command]
+pub async fn ocr_selection(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    language: Option<String>,
+    redactions: Option<Vec<Redaction>>,
+    sidecar: Option<OcrSidecar>,
+) -> Result<OcrResult, String> {
+    let (image_data, scale_factor) = {
+        let pending = state.pending_screenshot.lock().unwrap();
+        let screenshot = pending.as_ref().ok_or("No pending screenshot")?;
+        (screenshot.image_data.clone(), screenshot.scale_factor)
+    };
+    let mut img = image::load_from_memory(&image_data).map_err(|e| e.to_string())?.to_rgba8();
+    redaction::apply(&mut img, &redactions.unwrap_or_default(), scale_factor)?;
+
+    let options = OcrOptions {
+        language: language.unwrap_or_else(|| read_ocr_options(&app).language),
+        write_sidecar: sidecar.is_some(),
+    };
+    let (text, words) = recognize(&app, &img, scale_factor, &options.language).await?;
+
+    if !text.is_empty() {
+        Clipboard::new()
+            .and_then(|mut clipboard| clipboard.set_text(text.clone()))
+            .map_err(|e| e.to_string())?;
+    }
+
+    let sidecar_path = match sidecar {
+        Some(sidecar) if !text.is_empty() => {
+            let save_dir = match sidecar.output_folder {
+                Some(folder) if !folder.is_empty() => PathBuf::from(folder),
+                _ => PathBuf::from(get_save_path(app.clone()).await?),
+            };
+            Some(write_sidecar(&save_dir, &sidecar.filename, &text)?.to_string_lossy().into_owned())
+        }
+        _ => None,
+    };
+
+    // Remember the language and the sidecar choice for next time
+    let store = app.store("settings.json").map_err(|e| e.to_string())?;
+    store.set("ocrOptions", serde_json::to_value(&options).unwrap());
+    store.save().map_err(|e| e.to_string())?;
+
+    println!(
+        "[LOG] {} OCR: {} characters copied to clipboard{}",
+        Local::now().format("%H:%M:%S%.3f"),
+        text.chars().count(),
+        sidecar_path.as_ref().map(|p| format!(", written to {}", p)).unwrap_or_default()
+    );
+    Ok(OcrResult { text, words, language: options.language, sidecar_path })
+}
@@ -0,0 +1,150 @@
+// Named region presets ("Grafana panel", "App window 1280x720") stored in settings.json,
+// captured directly from the tray or applied to the current selection from the dialog.
+
+use crate::repeat_capture::{self, LastAction};
+use crate::{read_save_path, resolve_output_options, AppState, SelectionBounds, TimestampOptions};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_store::StoreExt;
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct RegionPreset {
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "displayId", default)]
+    pub display_id: Option<u32>, // None = primary display
+    pub bounds: SelectionBounds, // Logical pixels relative to the display
+    #[serde(rename = "timestampOptions", default)]
+    pub timestamp_options: Option<TimestampOptions>, // None = stored options
+    #[serde(rename = "imageFormat", default)]
+    pub image_format: Option<String>, // None = stored format
+    #[serde(rename = "outputFolder", default)]
+    pub output_folder: Option<String>, // None = save path
+}
+
+// Event payload sent after a preset capture from the tray
+#[derive(Clone, Serialize)]
+pub struct PresetCapturePayload {
+    pub id: String,
+    pub name: String,
+    pub path: Option<String>,
+    pub error: Option<String>,
+}
+
+pub fn read_presets(app: &AppHandle) -> Vec<RegionPreset> {
+    app.store("settings.json")
+        .ok()
+        .and_then(|store| store.get("regionPresets"))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+fn validate(preset: &RegionPreset) -> Result<(), String> {
+    if preset.name.trim().is_empty() {
+        return Err("Preset name is empty".to_string());
+    }
+    if preset.bounds.width <= 0 || preset.bounds.height <= 0 {
+        return Err(format!(
+            "\"{}\": region is empty ({}x{})",
+            preset.name, preset.bounds.width, preset.bounds.height
+        ));
+    }
+    Ok(())
+}
+
+// Validate, assign missing ids, store, and refresh the tray submenu
+fn write_presets(app: &AppHandle, mut presets: Vec<RegionPreset>) -> Result<Vec<RegionPreset>, String> {
+    for preset in presets.iter_mut() {
+        validate(preset)?;
+        if preset.id.is_empty() {
+            preset.id = uuid::Uuid::new_v4().simple().to_string();
+        }
+    }
+
+    let store = app.store("settings.json").map_err(|e| e.to_string())?;
+    store.set("regionPresets", serde_json::to_value(&presets).unwrap());
+    store.save().map_err(|e| e.to_string())?;
+
+    crate::refresh_tray_menu(app);
+    Ok(presets)
+}
+
+// Capture a preset without any UI, with its own options (stored ones otherwise)
+pub fn capture(app: &AppHandle, id: &str) -> Result<PathBuf, String> {
+    let preset = read_presets(app)
+        .into_iter()
+        .find(|p| p.id == id)
+        .ok_or_else(|| format!("Preset {} not found", id))?;
+    println!("[LOG] {} Capturing preset \"{}\"", Local::now().format("%H:%M:%S%.3f"), preset.name);
+
+    let (timestamp_options, image_format) = resolve_output_options(app, &preset.timestamp_options, &preset.image_format)?;
+    let save_dir = match &preset.output_folder {
+        Some(folder) if !folder.is_empty() => PathBuf::from(folder),
+        _ => PathBuf::from(read_save_path(app)?),
+    };
+
+    let path = repeat_capture::capture_and_deliver(
+        app,
+        preset.display_id,
+        &preset.bounds,
+        &timestamp_options,
+        &image_format,
+        &save_dir,
+        LastAction::Save,
+    )?;
+    path.ok_or_else(|| "Preset capture was not saved".to_string())
+}
+
+// Capture and report the result with a "preset-capture" event (tray)
+pub fn capture_and_notify(app: &AppHandle, id: &str) {
+    let name = read_presets(app)
+        .into_iter()
+        .find(|p| p.id == id)
+        .map(|p| p.name)
+        .unwrap_or_default();
+    let (path, error) = match capture(app, id) {
+        Ok(path) => (Some(path.to_string_lossy().to_string()), None),
+        Err(e) => {
+            println!("[LOG] {} Preset capture failed: {}", Local::now().format("%H:%M:%S%.3f"), e);
+            (None, Some(e))
+        }
+    };
+    let _ = app.emit("preset-capture", PresetCapturePayload { id: id.to_string(), name, path, error });
+}
+
+#[tauri::command]
+pub async fn get_region_presets(app: AppHandle) -> Result<Vec<RegionPreset>, String> {
+    Ok(read_presets(&app))
+}
+
+#[tauri::command]
+pub async fn set_region_presets(app: AppHandle, presets: Vec<RegionPreset>) -> Result<Vec<RegionPreset>, String> {
+    write_presets(&app, presets)
+}
+
+// Add a preset from the dialog; without a display, the display of the current capture is used
+#[tauri::command]
+pub async fn add_region_preset(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    preset: RegionPreset,
+) -> Result<RegionPreset, String> {
+    let mut preset = preset;
+    if preset.display_id.is_none() {
+        preset.display_id = *state.current_display.lock().unwrap();
+    }
+    preset.id = String::new();
+
+    let mut presets = read_presets(&app);
+    presets.push(preset);
+    let presets = write_presets(&app, presets)?;
+    Ok(presets.last().cloned().unwrap())
+}
+
+#[tauri::command]
+pub async fn capture_region_preset(app: AppHandle, id: String) -> Result<String, String> {
+    Ok(capture(&app, &id)?.to_string_lossy().to_string())
+}
@@ -0,0 +1,157 @@
+// Privacy zones: screen areas (notification corner, password manager side panel) that must
+// never appear in a capture. The capture source of AppState is wrapped in a PrivacyFilter,
+// so every RawScreenshot (interactive, repeat, preset, time-lapse, watch, scheduled,
+// scrolling) is redacted as soon as it is captured, before it is stored, shown in the
+// selection window or written anywhere.
+
+use crate::capture::{CaptureSource, DisplayInfo};
+use crate::redaction::{self, Redaction, RedactionMode};
+use crate::{AppState, RawScreenshot, SelectionBounds};
+use chrono::Local;
+use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager, State};
+use tauri_plugin_store::StoreExt;
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct PrivacyZone {
+    #[serde(default)]
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(rename = "displayId", default)]
+    pub display_id: Option<u32>, // None = primary display
+    pub bounds: SelectionBounds, // Logical pixels relative to the display
+    #[serde(flatten)]
+    pub mode: RedactionMode,
+}
+
+pub type SharedZones = Arc<Mutex<Vec<PrivacyZone>>>;
+
+pub fn read_zones(app: &AppHandle) -> Vec<PrivacyZone> {
+    app.store("settings.json")
+        .ok()
+        .and_then(|store| store.get("privacyZones"))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+// Load the stored zones into the capture filter (at startup)
+pub fn load(app: &AppHandle) {
+    let zones = read_zones(app);
+    println!("[LOG] {} {} privacy zone(s) loaded", Local::now().format("%H:%M:%S%.3f"), zones.len());
+    let state: State<'_, AppState> = app.state();
+    *state.privacy_zones.lock().unwrap() = zones;
+}
+
+fn validate(zone: &PrivacyZone) -> Result<(), String> {
+    if zone.bounds.width <= 0 || zone.bounds.height <= 0 {
+        return Err(format!(
+            "\"{}\": zone is empty ({}x{})",
+            zone.name, zone.bounds.width, zone.bounds.height
+        ));
+    }
+    // Catch invalid colors here rather than failing every capture
+    let mut probe = RgbaImage::new(1, 1);
+    let redaction = Redaction { x: 0.0, y: 0.0, width: 1.0, height: 1.0, mode: zone.mode.clone() };
+    redaction::apply(&mut probe, &[redaction], 1.0).map_err(|e| format!("\"{}\": {}", zone.name, e))
+}
+
+// Capture source redacting the privacy zones of each frame it returns
+pub struct PrivacyFilter {
+    inner: Box<dyn CaptureSource>,
+    zones: SharedZones,
+}
+
+impl PrivacyFilter {
+    pub fn new(inner: Box<dyn CaptureSource>, zones: SharedZones) -> Self {
+        Self { inner, zones }
+    }
+
+    // Redact the zones of `display` in a frame whose top-left corner is at
+    // `origin_x`/`origin_y` (logical pixels relative to the display)
+    fn redact(&self, mut raw: RawScreenshot, display: &DisplayInfo, origin_x: i32, origin_y: i32) -> Result<RawScreenshot, String> {
+        let redactions: Vec<Redaction> = self
+            .zones
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|zone| zone.display_id.map_or(display.is_primary, |id| id == display.id))
+            .map(|zone| Redaction {
+                x: (zone.bounds.x - origin_x) as f32,
+                y: (zone.bounds.y - origin_y) as f32,
+                width: zone.bounds.width as f32,
+                height: zone.bounds.height as f32,
+                mode: zone.mode.clone(),
+            })
+            .collect();
+        if redactions.is_empty() {
+            return Ok(raw);
+        }
+
+        // A frame that cannot be redacted is not returned at all
+        let mut img = RgbaImage::from_raw(raw.width, raw.height, std::mem::take(&mut raw.data))
+            .ok_or("Invalid frame size, privacy zones not applied")?;
+        redaction::apply(&mut img, &redactions, raw.scale_factor)?;
+        raw.data = img.into_raw();
+        Ok(raw)
+    }
+}
+
+impl CaptureSource for PrivacyFilter {
+    fn displays(&self) -> Result<Vec<DisplayInfo>, String> {
+        self.inner.displays()
+    }
+
+    fn capture_display(&self, display: &DisplayInfo) -> Result<RawScreenshot, String> {
+        let raw = self.inner.capture_display(display)?;
+        self.redact(raw, display, 0, 0)
+    }
+
+    fn capture_area(
+        &self,
+        display: &DisplayInfo,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+    ) -> Result<RawScreenshot, String> {
+        let raw = self.inner.capture_area(display, x, y, width, height)?;
+        self.redact(raw, display, x, y)
+    }
+
+    fn capture_display_alternate(&self, display: &DisplayInfo) -> Result<RawScreenshot, String> {
+        let raw = self.inner.capture_display_alternate(display)?;
+        self.redact(raw, display, 0, 0)
+    }
+}
+
+#[tauri::command]
+pub async fn get_privacy_zones(app: AppHandle) -> Result<Vec<PrivacyZone>, String> {
+    Ok(read_zones(&app))
+}
+
+// Validate, assign missing ids, store, and apply to the next captures
+#[tauri::command]
+pub async fn set_privacy_zones(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    zones: Vec<PrivacyZone>,
+) -> Result<Vec<PrivacyZone>, String> {
+    let mut zones = zones;
+    for zone in zones.iter_mut() {
+        validate(zone)?;
+        if zone.id.is_empty() {
+            zone.id = uuid::Uuid::new_v4().simple().to_string();
+        }
+    }
+
+    let store = app.store("settings.json").map_err(|e| e.to_string())?;
+    store.set("privacyZones", serde_json::to_value(&zones).unwrap());
+    store.save().map_err(|e| e.to_string())?;
+
+    *state.privacy_zones.lock().unwrap() = zones.clone();
+    println!("[LOG] {} {} privacy zone(s) saved", Local::now().format("%H:%M:%S%.3f"), zones.len());
+    Ok(zones)
+}
@@ -0,0 +1,225 @@
+// Redaction regions (pixelate, blur, opaque fill) drawn in the selection window and carried
+// in SaveData. They are applied to the pixels of the cropped image before annotations,
+// timestamp and encoding, so neither the saved file nor the clipboard copy keeps the
+// original content of a region.
+//
+// Coordinates are logical pixels relative to the top-left corner of the selection, like
+// annotations; they are scaled by the scale factor of the capture and rounded outwards.
+
+use crate::annotations::parse_color;
+use image::{imageops, RgbaImage};
+use serde::{Deserialize, Serialize};
+
+// Smallest block (physical pixels): below it, pixelated text can be guessed back
+const MIN_BLOCK_SIZE: u32 = 6;
+
+fn default_block_size() -> u32 {
+    16
+}
+
+fn default_radius() -> f32 {
+    16.0
+}
+
+fn default_fill_color() -> String {
+    "#000000".to_string()
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+#[serde(tag = "mode")]
+pub enum RedactionMode {
+    #[serde(rename = "pixelate")]
+    Pixelate {
+        #[serde(rename = "blockSize", default = "default_block_size")]
+        block_size: u32, // Logical pixels
+    },
+    #[serde(rename = "blur")]
+    Blur {
+        #[serde(default = "default_radius")]
+        radius: f32, // Logical pixels
+    },
+    #[serde(rename = "fill")]
+    Fill {
+        #[serde(default = "default_fill_color")]
+        color: String, // "#rrggbb", always opaque
+    },
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Redaction {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    #[serde(flatten)]
+    pub mode: RedactionMode,
+}
+
+// Physical rectangle (x, y, width, height) of a region, None when outside the image
+fn physical_rect(redaction: &Redaction, scale: f32, width: u32, height: u32) -> Option<(u32, u32, u32, u32)> {
+    let left = (redaction.x * scale).floor().max(0.0) as u32;
+    let top = (redaction.y * scale).floor().max(0.0) as u32;
+    let right = (((redaction.x + redaction.width) * scale).ceil().max(0.0) as u32).min(width);
+    let bottom = (((redaction.y + redaction.height) * scale).ceil().max(0.0) as u32).min(height);
+    (right > left && bottom > top).then(|| (left, top, right - left, bottom - top))
+}
+
+// Replace each block of the rectangle by its mean color (blocks aligned on the rectangle)
+fn pixelate(img: &mut RgbaImage, (x, y, width, height): (u32, u32, u32, u32), block: u32) {
+    let block = block.max(MIN_BLOCK_SIZE);
+    for block_y in (y..y + height).step_by(block as usize) {
+        for block_x in (x..x + width).step_by(block as usize) {
+            let block_right = (block_x + block).min(x + width);
+            let block_bottom = (block_y + block).min(y + height);
+
+            let mut sum = [0u64; 4];
+            for py in block_y..block_bottom {
+                for px in block_x..block_right {
+                    for (total, value) in sum.iter_mut().zip(img.get_pixel(px, py).0) {
+                        *total += value as u64;
+                    }
+                }
+            }
+            let count = ((block_right - block_x) * (block_bottom - block_y)) as u64;
+            let mean = sum.map(|total| ((total + count / 2) / count) as u8);
+
+            for py in block_y..block_bottom {
+                for px in block_x..block_right {
+                    img.get_pixel_mut(px, py).0 = mean;
+                }
+            }
+        }
+    }
+}
+
+// Gaussian blur alone can be partly inverted (deconvolution): the region is pixelated
+// with blocks of the blur radius first, then blurred on its own, so only block means
+// (and no pixel outside the region) reach the result
+fn blur(img: &mut RgbaImage, rect: (u32, u32, u32, u32), radius: f32) {
+    pixelate(img, rect, radius.round() as u32);
+    let (x, y, width, height) = rect;
+    let region = imageops::crop_imm(img, x, y, width, height).to_image();
+    let blurred = imageops::blur(&region, (radius / 2.0).max(1.0));
+    imageops::replace(img, &blurred, x as i64, y as i64);
+}
+
+// Alpha of the color is ignored: a see-through fill would not hide anything
+fn fill(img: &mut RgbaImage, (x, y, width, height): (u32, u32, u32, u32), color: [u8; 4]) {
+    for py in y..y + height {
+        for px in x..x + width {
+            img.get_pixel_mut(px, py).0 = [color[0], color[1], color[2], 255];
+        }
+    }
+}
+
+// Apply the redactions to the image; `scale_factor` maps their logical pixels to the
+// physical pixels of the image
+pub fn apply(img: &mut RgbaImage, redactions: &[Redaction], scale_factor: f32) -> Result<(), String> {
+    let scale = if scale_factor > 0.0 { scale_factor } else { 1.0 };
+    let (width, height) = img.dimensions();
+    for redaction in redactions {
+        let Some(rect) = physical_rect(redaction, scale, width, height) else {
+            continue;
+        };
+        match &redaction.mode {
+            RedactionMode::Pixelate { block_size } => pixelate(img, rect, (*block_size as f32 * scale).round() as u32),
+            RedactionMode::Blur { radius } => blur(img, rect, radius * scale),
+            RedactionMode::Fill { color } => fill(img, rect, parse_color(color)?),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    // Region used by every test: logical (10.25, 10) 30x20 at scale 2, rounded outwards to
+    // physical (20, 20) 61x40
+    const SCALE: f32 = 2.0;
+    const RECT: (u32, u32, u32, u32) = (20, 20, 61, 40);
+
+    // High-entropy image (xorshift noise): no two neighbouring pixels alike
+    fn noise(seed: u32) -> RgbaImage {
+        let mut state = seed;
+        RgbaImage::from_fn(120, 90, |_, _| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            Rgba([state as u8, (state >> 8) as u8, (state >> 16) as u8, 255])
+        })
+    }
+
+    fn redacted(img: &RgbaImage, mode: RedactionMode) -> RgbaImage {
+        let redaction = Redaction { x: 10.25, y: 10.0, width: 30.0, height: 20.0, mode };
+        let mut result = img.clone();
+        apply(&mut result, &[redaction], SCALE).unwrap();
+        result
+    }
+
+    fn inside(x: u32, y: u32) -> bool {
+        let (left, top, width, height) = RECT;
+        x >= left && x < left + width && y >= top && y < top + height
+    }
+
+    // Same image with the pixels of each block of the region in reverse order: a different
+    // pattern with the same block means
+    fn reverse_blocks(img: &RgbaImage, block: u32) -> RgbaImage {
+        let (x, y, width, height) = RECT;
+        let mut result = img.clone();
+        for block_y in (y..y + height).step_by(block as usize) {
+            for block_x in (x..x + width).step_by(block as usize) {
+                let right = (block_x + block).min(x + width);
+                let bottom = (block_y + block).min(y + height);
+                let pixels: Vec<_> = (block_y..bottom)
+                    .flat_map(|py| (block_x..right).map(move |px| (px, py)))
+                    .collect();
+                for (&(px, py), &(sx, sy)) in pixels.iter().zip(pixels.iter().rev()) {
+                    result.put_pixel(px, py, *img.get_pixel(sx, sy));
+                }
+            }
+        }
+        result
+    }
+
+    fn assert_redacted(original: &RgbaImage, result: &RgbaImage) {
+        for (x, y, pixel) in result.enumerate_pixels() {
+            if inside(x, y) {
+                assert_ne!(pixel, original.get_pixel(x, y), "original pixel kept at {},{}", x, y);
+            } else {
+                assert_eq!(pixel, original.get_pixel(x, y), "pixel outside the region changed at {},{}", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn pixelate_keeps_only_block_means() {
+        let original = noise(1);
+        let mode = || RedactionMode::Pixelate { block_size: 8 };
+        let result = redacted(&original, mode());
+        assert_redacted(&original, &result);
+        assert_eq!(result, redacted(&reverse_blocks(&original, 16), mode()));
+    }
+
+    #[test]
+    fn blur_keeps_only_block_means() {
+        let original = noise(2);
+        let mode = || RedactionMode::Blur { radius: 8.0 };
+        let result = redacted(&original, mode());
+        assert_redacted(&original, &result);
+        assert_eq!(result, redacted(&reverse_blocks(&original, 16), mode()));
+    }
+
+    #[test]
+    fn fill_covers_the_region() {
+        let original = noise(3);
+        let result = redacted(&original, RedactionMode::Fill { color: "#102030".to_string() });
+        assert_redacted(&original, &result);
+        for (x, y, pixel) in result.enumerate_pixels() {
+            if inside(x, y) {
+                assert_eq!(pixel, &Rgba([0x10, 0x20, 0x30, 255]));
+            }
+        }
+    }
+}
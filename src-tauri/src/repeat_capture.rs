@@ -0,0 +1,152 @@
+// Repeat last region: the last selection (with its display) and the last save/copy
+// choice are persisted, so the same area can be captured again without any UI.
+
+use crate::{
+    add_timestamp_to_rgba, capture, copy_rgba_to_clipboard, cursor, generate_default_filename,
+    read_image_format, read_save_path, read_timestamp_options, AppState, ClipboardErrorPayload,
+    SelectionBounds, TimestampOptions,
+};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_store::StoreExt;
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct LastRegion {
+    #[serde(rename = "displayId")]
+    pub display_id: u32,
+    pub bounds: SelectionBounds, // Logical pixels relative to the display
+}
+
+// What the last capture did with the image
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq, Default)]
+pub enum LastAction {
+    #[serde(rename = "save")]
+    #[default]
+    Save,
+    #[serde(rename = "copy")]
+    Copy,
+}
+
+// Event payload sent after a repeated capture
+#[derive(Clone, Serialize)]
+pub struct RepeatCapturePayload {
+    pub path: Option<String>, // None when only copied to the clipboard
+    pub error: Option<String>,
+}
+
+pub fn read_last_region(app: &AppHandle) -> Option<LastRegion> {
+    let store = app.store("settings.json").ok()?;
+    store
+        .get("lastRegion")
+        .and_then(|v| serde_json::from_value(v).ok())
+}
+
+fn read_last_action(app: &AppHandle) -> LastAction {
+    app.store("settings.json")
+        .ok()
+        .and_then(|store| store.get("lastCaptureAction"))
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default()
+}
+
+pub fn remember_region(app: &AppHandle, display_id: u32, bounds: &SelectionBounds) -> Result<(), String> {
+    let region = LastRegion { display_id, bounds: bounds.clone() };
+    let store = app.store("settings.json").map_err(|e| e.to_string())?;
+    store.set("lastRegion", serde_json::to_value(&region).unwrap());
+    store.save().map_err(|e| e.to_string())
+}
+
+pub fn remember_action(app: &AppHandle, action: LastAction) -> Result<(), String> {
+    let store = app.store("settings.json").map_err(|e| e.to_string())?;
+    store.set("lastCaptureAction", serde_json::to_value(action).unwrap());
+    store.save().map_err(|e| e.to_string())
+}
+
+// Capture the last region again, stamp it with the last options and save or copy it
+pub fn run(app: &AppHandle) -> Result<Option<PathBuf>, String> {
+    println!("[LOG] {} Repeating last region capture", Local::now().format("%H:%M:%S%.3f"));
+    let region = read_last_region(app).ok_or("No previous region to capture")?;
+    let timestamp_options = read_timestamp_options(app)?;
+    let image_format = read_image_format(app)?;
+    let save_dir = PathBuf::from(read_save_path(app)?);
+    capture_and_deliver(
+        app,
+        Some(region.display_id),
+        &region.bounds,
+        &timestamp_options,
+        &image_format,
+        &save_dir,
+        read_last_action(app),
+    )
+}
+
+// Headless pipeline: capture a region, stamp it, then save it into `save_dir`
+// (returns the path) or only copy it to the clipboard (returns None)
+pub fn capture_and_deliver(
+    app: &AppHandle,
+    display_id: Option<u32>,
+    bounds: &SelectionBounds,
+    timestamp_options: &TimestampOptions,
+    image_format: &str,
+    save_dir: &Path,
+    action: LastAction,
+) -> Result<Option<PathBuf>, String> {
+    let state: State<'_, AppState> = app.state();
+    let cursor = cursor::read_cursor_options(app);
+    let img = capture::capture_region(state.capture_source.as_ref(), display_id, Some(bounds), cursor)?;
+    let processed = add_timestamp_to_rgba(img, timestamp_options, image_format)?;
+
+    if action == LastAction::Copy {
+        copy_rgba_to_clipboard(processed.rgba_pixels, processed.width, processed.height)?;
+        println!("[LOG] {} Region copied to clipboard", Local::now().format("%H:%M:%S%.3f"));
+        return Ok(None);
+    }
+
+    fs::create_dir_all(save_dir).map_err(|e| e.to_string())?;
+    let extension = if image_format == "png" { "png" } else { "jpg" };
+    let full_path = save_dir.join(format!("{}.{}", generate_default_filename(), extension));
+    fs::write(&full_path, &processed.encoded_bytes).map_err(|e| e.to_string())?;
+    println!("[LOG] {} Region saved to {}", Local::now().format("%H:%M:%S%.3f"), full_path.display());
+
+    // Same clipboard behaviour as a regular save
+    let clipboard_enabled = app.store("settings.json")
+        .ok()
+        .and_then(|store| store.get("clipboardCopyEnabled"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+    if clipboard_enabled {
+        if let Err(e) = copy_rgba_to_clipboard(processed.rgba_pixels, processed.width, processed.height) {
+            println!("[LOG] {} Clipboard copy failed: {}", Local::now().format("%H:%M:%S%.3f"), e);
+            let _ = app.emit("clipboard-copy-failed", ClipboardErrorPayload {
+                message: "Screenshot saved but clipboard copy failed".to_string(),
+            });
+        }
+    }
+
+    Ok(Some(full_path))
+}
+
+// Run and report the result with a "repeat-capture" event (tray and hotkey)
+pub fn run_and_notify(app: &AppHandle) {
+    let (path, error) = match run(app) {
+        Ok(path) => (path.map(|p| p.to_string_lossy().to_string()), None),
+        Err(e) => {
+            println!("[LOG] {} Repeat capture failed: {}", Local::now().format("%H:%M:%S%.3f"), e);
+            (None, Some(e))
+        }
+    };
+    let _ = app.emit("repeat-capture", RepeatCapturePayload { path, error });
+}
+
+#[tauri::command]
+pub async fn capture_last_region(app: AppHandle) -> Result<Option<String>, String> {
+    Ok(run(&app)?.map(|p| p.to_string_lossy().to_string()))
+}
+
+#[tauri::command]
+pub async fn get_last_region(app: AppHandle) -> Result<Option<LastRegion>, String> {
+    Ok(read_last_region(&app))
+}
@@ -0,0 +1,359 @@
+// Scheduled captures: cron expressions stored in settings.json, executed in the
+// background through the capture/stamp/save pipeline, without any UI.
+//
+// Expressions use the `cron` crate syntax with an optional seconds field:
+// "30 17 * * Mon-Fri" or "0 30 17 * * Mon-Fri" = every weekday at 17:30.
+// Numeric days of week follow crontab (0 or 7 = Sunday, 1 = Monday), and are translated
+// to the `cron` crate numbering (1 = Sunday) before parsing.
+
+use crate::{
+    add_timestamp_to_rgba, capture, cursor, read_save_path, resolve_output_options, AppState, SelectionBounds,
+    TimestampOptions,
+};
+use chrono::{DateTime, Local};
+use cron::Schedule;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_store::StoreExt;
+
+// How often the scheduler wakes up
+const TICK: Duration = Duration::from_secs(5);
+// Runs older than this when noticed are considered missed (machine asleep, clock change)
+const MISSED_GRACE_SECS: i64 = 60;
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ScheduledCapture {
+    pub id: String,
+    pub name: String,
+    // One or more cron expressions ("0 9 * * Mon-Fri", "30 17 * * Mon-Fri")
+    pub expressions: Vec<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    #[serde(rename = "displayId", default)]
+    pub display_id: Option<u32>, // None = primary display
+    #[serde(default)]
+    pub region: Option<SelectionBounds>, // None = whole display
+    #[serde(rename = "outputFolder", default)]
+    pub output_folder: Option<String>, // None = save path
+    #[serde(rename = "timestampOptions", default)]
+    pub timestamp_options: Option<TimestampOptions>, // None = stored options
+    #[serde(rename = "imageFormat", default)]
+    pub image_format: Option<String>, // None = stored format
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+// What to do with runs missed while the machine was asleep
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, Eq, Default)]
+pub enum MissedRunPolicy {
+    // Run once on wake-up, however many runs were missed
+    #[serde(rename = "catch-up")]
+    #[default]
+    CatchUp,
+    // Ignore missed runs and wait for the next one
+    #[serde(rename = "skip")]
+    Skip,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct SchedulerSettings {
+    pub schedules: Vec<ScheduledCapture>,
+    #[serde(rename = "missedPolicy")]
+    pub missed_policy: MissedRunPolicy,
+}
+
+// Event payload sent after each scheduled capture
+#[derive(Clone, Serialize)]
+pub struct ScheduledCapturePayload {
+    pub id: String,
+    pub name: String,
+    pub path: Option<String>,
+    pub error: Option<String>,
+    #[serde(rename = "catchUp")]
+    pub catch_up: bool,
+}
+
+// Crontab day of week (0-7, both 0 and 7 = Sunday)
+fn crontab_day(value: &str) -> Result<u32, String> {
+    match value.parse::<u32>() {
+        Ok(day) if day <= 7 => Ok(day),
+        _ => Err(format!("Invalid day of week: {}", value)),
+    }
+}
+
+// Translate the numeric items of a crontab day-of-week field ("1-5", "0,6", "*/2") to the
+// `cron` crate numbering, as explicit day lists; names ("Mon-Fri") and "*" are kept
+fn translate_day_of_week(field: &str) -> Result<String, String> {
+    let mut items = Vec::new();
+    for item in field.split(',') {
+        let (base, step) = match item.split_once('/') {
+            Some((base, step)) => match step.parse::<u32>() {
+                Ok(step) if step > 0 => (base, Some(step)),
+                _ => return Err(format!("Invalid day of week step: {}", item)),
+            },
+            None => (item, None),
+        };
+        if base.chars().any(|c| c.is_ascii_alphabetic()) {
+            if base.chars().any(|c| c.is_ascii_digit()) {
+                return Err(format!("Mixed day names and numbers: {}", item));
+            }
+            items.push(item.to_string());
+            continue;
+        }
+        if base == "*" && step.is_none() {
+            items.push(item.to_string());
+            continue;
+        }
+
+        let (first, last) = match base {
+            "*" => (0, 6),
+            _ => match base.split_once('-') {
+                Some((first, last)) => (crontab_day(first)?, crontab_day(last)?),
+                // "n/step" runs from n to the end of the week
+                None => {
+                    let day = crontab_day(base)?;
+                    (day, if step.is_some() { 7 } else { day })
+                }
+            },
+        };
+        if first > last {
+            return Err(format!("Invalid day of week range: {}", item));
+        }
+        let mut days: Vec<u32> = (first..=last)
+            .step_by(step.unwrap_or(1) as usize)
+            .map(|day| day % 7 + 1) // Sunday (0 or 7) = 1, Monday = 2...
+            .collect();
+        days.sort_unstable();
+        days.dedup();
+        items.extend(days.iter().map(u32::to_string));
+    }
+    Ok(items.join(","))
+}
+
+// Parse an expression, accepting the usual 5-field crontab form (seconds = 0)
+pub fn parse_expression(expression: &str) -> Result<Schedule, String> {
+    let expression = expression.trim();
+    let mut fields: Vec<String> = expression.split_whitespace().map(str::to_string).collect();
+    if fields.len() == 5 {
+        fields.insert(0, "0".to_string());
+    }
+    let invalid = |e: String| format!("Invalid cron expression \"{}\": {}", expression, e);
+    if let Some(day_of_week) = fields.get_mut(5) {
+        *day_of_week = translate_day_of_week(day_of_week).map_err(invalid)?;
+    }
+    Schedule::from_str(&fields.join(" ")).map_err(|e| invalid(e.to_string()))
+}
+
+pub fn read_settings(app: &AppHandle) -> Result<SchedulerSettings, String> {
+    let store = app.store("settings.json").map_err(|e| e.to_string())?;
+    let schedules = store
+        .get("schedules")
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default();
+    let missed_policy = store
+        .get("scheduleMissedPolicy")
+        .and_then(|v| serde_json::from_value(v).ok())
+        .unwrap_or_default();
+    Ok(SchedulerSettings { schedules, missed_policy })
+}
+
+// Start the scheduler thread (once, at startup)
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let mut last_check = Local::now();
+        loop {
+            std::thread::sleep(TICK);
+            let now = Local::now();
+            tick(&app, last_check, now);
+            last_check = now;
+        }
+    });
+}
+
+// Run the schedules due in (last_check, now]
+fn tick(app: &AppHandle, last_check: DateTime<Local>, now: DateTime<Local>) {
+    let settings = match read_settings(app) {
+        Ok(settings) => settings,
+        Err(e) => {
+            println!("[SCHEDULER] {} Cannot read settings: {}", Local::now().format("%H:%M:%S%.3f"), e);
+            return;
+        }
+    };
+
+    for scheduled in settings.schedules.iter().filter(|s| s.enabled) {
+        // Most recent due run over all expressions of the entry
+        let due = scheduled
+            .expressions
+            .iter()
+            .filter_map(|expression| parse_expression(expression).ok())
+            .filter_map(|schedule| schedule.after(&last_check).take_while(|t| *t <= now).last())
+            .max();
+        let Some(due) = due else {
+            continue;
+        };
+
+        let missed = (now - due).num_seconds() > MISSED_GRACE_SECS;
+        if missed && settings.missed_policy == MissedRunPolicy::Skip {
+            println!(
+                "[SCHEDULER] {} Skipping missed run of \"{}\" ({})",
+                Local::now().format("%H:%M:%S%.3f"),
+                scheduled.name,
+                due.format("%Y-%m-%d %H:%M:%S")
+            );
+            continue;
+        }
+
+        println!(
+            "[SCHEDULER] {} Running \"{}\"{}",
+            Local::now().format("%H:%M:%S%.3f"),
+            scheduled.name,
+            if missed { " (catch-up)" } else { "" }
+        );
+        let result = run_capture(app, scheduled);
+        if let Err(e) = &result {
+            println!("[SCHEDULER] {} \"{}\" failed: {}", Local::now().format("%H:%M:%S%.3f"), scheduled.name, e);
+        }
+        let (path, error) = match result {
+            Ok(path) => (Some(path.to_string_lossy().to_string()), None),
+            Err(e) => (None, Some(e)),
+        };
+        let _ = app.emit("scheduled-capture", ScheduledCapturePayload {
+            id: scheduled.id.clone(),
+            name: scheduled.name.clone(),
+            path,
+            error,
+            catch_up: missed,
+        });
+    }
+}
+
+// Capture, stamp and save one scheduled entry
+fn run_capture(app: &AppHandle, scheduled: &ScheduledCapture) -> Result<PathBuf, String> {
+    let state: State<'_, AppState> = app.state();
+    let cursor = cursor::read_cursor_options(app);
+    let img = capture::capture_region(state.capture_source.as_ref(), scheduled.display_id, scheduled.region.as_ref(), cursor)?;
+
+    let (timestamp_options, image_format) =
+        resolve_output_options(app, &scheduled.timestamp_options, &scheduled.image_format)?;
+    let processed = add_timestamp_to_rgba(img, &timestamp_options, &image_format)?;
+
+    let save_dir = match &scheduled.output_folder {
+        Some(folder) if !folder.is_empty() => PathBuf::from(folder),
+        _ => PathBuf::from(read_save_path(app)?),
+    };
+    fs::create_dir_all(&save_dir).map_err(|e| e.to_string())?;
+
+    let name: String = scheduled
+        .name
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    let extension = if image_format == "png" { "png" } else { "jpg" };
+    let filename = format!("{}_{}.{}", Local::now().format("%Y-%m-%dT%H-%M-%S"), name, extension);
+    let full_path = save_dir.join(filename);
+    fs::write(&full_path, &processed.encoded_bytes).map_err(|e| e.to_string())?;
+    Ok(full_path)
+}
+
+#[tauri::command]
+pub async fn get_scheduler_settings(app: AppHandle) -> Result<SchedulerSettings, String> {
+    read_settings(&app)
+}
+
+#[tauri::command]
+pub async fn set_scheduler_settings(app: AppHandle, settings: SchedulerSettings) -> Result<SchedulerSettings, String> {
+    let mut settings = settings;
+    for scheduled in settings.schedules.iter_mut() {
+        if scheduled.expressions.is_empty() {
+            return Err(format!("\"{}\" has no cron expression", scheduled.name));
+        }
+        for expression in &scheduled.expressions {
+            parse_expression(expression)?;
+        }
+        if scheduled.id.is_empty() {
+            scheduled.id = uuid::Uuid::new_v4().simple().to_string();
+        }
+    }
+
+    let store = app.store("settings.json").map_err(|e| e.to_string())?;
+    store.set("schedules", serde_json::to_value(&settings.schedules).unwrap());
+    store.set("scheduleMissedPolicy", serde_json::to_value(settings.missed_policy).unwrap());
+    store.save().map_err(|e| e.to_string())?;
+    Ok(settings)
+}
+
+// Next run times of an expression, to preview it while editing
+#[tauri::command]
+pub async fn preview_cron_expression(expression: String, count: Option<usize>) -> Result<Vec<String>, String> {
+    let schedule = parse_expression(&expression)?;
+    Ok(schedule
+        .upcoming(Local)
+        .take(count.unwrap_or(3))
+        .map(|t| t.format("%a %d/%m/%Y %H:%M:%S").to_string())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Datelike, TimeZone, Weekday};
+
+    // Weekdays of the runs of an expression during the week after Sunday 2026-01-04, noon
+    fn run_days(expression: &str) -> Vec<Weekday> {
+        let start = Local.with_ymd_and_hms(2026, 1, 4, 12, 0, 0).unwrap(); // Sunday noon
+        let mut days: Vec<Weekday> = parse_expression(expression)
+            .unwrap()
+            .after(&start)
+            .take_while(|t| *t < start + chrono::Duration::days(7))
+            .map(|t| t.weekday())
+            .collect();
+        days.dedup();
+        days
+    }
+
+    #[test]
+    fn numeric_range_is_monday_to_friday() {
+        use Weekday::*;
+        assert_eq!(run_days("0 9 * * 1-5"), vec![Mon, Tue, Wed, Thu, Fri]);
+        assert_eq!(run_days("0 0 9 * * 1-5"), vec![Mon, Tue, Wed, Thu, Fri]);
+    }
+
+    #[test]
+    fn zero_and_seven_are_sunday() {
+        assert_eq!(run_days("0 9 * * 0"), vec![Weekday::Sun]);
+        assert_eq!(run_days("0 9 * * 7"), vec![Weekday::Sun]);
+        assert_eq!(run_days("0 9 * * 0,7"), vec![Weekday::Sun]);
+        assert_eq!(run_days("0 9 * * 5-7"), vec![Weekday::Fri, Weekday::Sat, Weekday::Sun]);
+    }
+
+    #[test]
+    fn named_days_are_kept() {
+        use Weekday::*;
+        assert_eq!(run_days("0 9 * * Mon-Fri"), vec![Mon, Tue, Wed, Thu, Fri]);
+        assert_eq!(run_days("0 9 * * Sun"), vec![Sun]);
+        assert_eq!(run_days("0 9 * * Sat,Sun"), vec![Sat, Sun]);
+        assert_eq!(run_days("0 9 * * *").len(), 7);
+    }
+
+    #[test]
+    fn numeric_steps_follow_crontab() {
+        use Weekday::*;
+        assert_eq!(run_days("0 9 * * */2"), vec![Tue, Thu, Sat, Sun]);
+        assert_eq!(run_days("0 9 * * 1-5/2"), vec![Mon, Wed, Fri]);
+    }
+
+    #[test]
+    fn invalid_days_are_rejected() {
+        assert!(parse_expression("0 9 * * 8").is_err());
+        assert!(parse_expression("0 9 * * 5-1").is_err());
+        assert!(parse_expression("0 9 * * 1-Fri").is_err());
+        assert!(parse_expression("0 9 * * */0").is_err());
+    }
+}
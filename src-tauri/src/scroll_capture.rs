@@ -0,0 +1,394 @@
+// Scrolling capture: the selected region is captured repeatedly while the user scrolls,
+// each frame is registered against the previous one (vertical offset) and its new rows
+// are appended to one tall image, which becomes the pending screenshot.
+//
+// Our windows (the dialog included) are hidden for the whole session, otherwise they would
+// be captured over the scrolled content; the session is ended from the tray menu.
+
+use crate::{capture, cursor, AppState, PendingScreenshot, SelectionBounds, TRAY_ID, TRAY_TOOLTIP};
+use chrono::Local;
+use image::{DynamicImage, ImageFormat, RgbaImage};
+use serde::Serialize;
+use std::io::Cursor;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State, WebviewWindow};
+
+// Delay between two captures
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+// Columns sampled per row for registration
+const PROFILE_WIDTH: u32 = 96;
+// Mean luma difference (0-255) under which two rows are considered identical
+const MATCH_THRESHOLD: f32 = 3.0;
+// Smallest overlap accepted, as a fraction of the frame height
+const MIN_OVERLAP_RATIO: f32 = 0.15;
+// Stop growing beyond this height (memory)
+const MAX_HEIGHT: u32 = 32_000;
+// The cursor would be stitched again in every frame and break the registration
+const NO_CURSOR: cursor::CursorOptions = cursor::CursorOptions { include: false, halo: false };
+
+// Sampled luma of each row
+fn row_profiles(img: &RgbaImage) -> Vec<Vec<u8>> {
+    let (width, height) = img.dimensions();
+    let columns = PROFILE_WIDTH.min(width).max(1);
+    (0..height)
+        .map(|y| {
+            (0..columns)
+                .map(|i| {
+                    let x = (i * width / columns).min(width - 1);
+                    let p = img.get_pixel(x, y);
+                    ((299 * p[0] as u32 + 587 * p[1] as u32 + 114 * p[2] as u32) / 1000) as u8
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn row_difference(a: &[u8], b: &[u8]) -> u32 {
+    a.iter().zip(b).map(|(x, y)| x.abs_diff(*y) as u32).sum()
+}
+
+// Vertical scroll between two frames of the same region: `Some(dy)` when the top of
+// `current` matches `previous` starting at row `dy` (0 = not scrolled), `None` when no
+// offset gives a good enough overlap
+pub fn find_vertical_offset(previous: &RgbaImage, current: &RgbaImage) -> Option<u32> {
+    if previous.dimensions() != current.dimensions() {
+        return None;
+    }
+    let height = current.height();
+    let before = row_profiles(previous);
+    let after = row_profiles(current);
+    let columns = before.first().map_or(1, |row| row.len()) as f32;
+    let min_overlap = ((height as f32 * MIN_OVERLAP_RATIO) as u32).max(1);
+
+    // Uniform rows (blank lines, backgrounds) match any offset: an overlap made only of
+    // them proves nothing
+    let informative: Vec<bool> = before
+        .iter()
+        .map(|row| {
+            let (min, max) = row.iter().fold((u8::MAX, 0), |(lo, hi), &v| (lo.min(v), hi.max(v)));
+            max - min > 8
+        })
+        .collect();
+
+    let mut best: Option<(u32, f32)> = None;
+    for dy in 0..=height.saturating_sub(min_overlap) {
+        let overlap = (height - dy) as usize;
+        if !informative[dy as usize..].iter().any(|&i| i) {
+            continue;
+        }
+        let total: u64 = (0..overlap)
+            .map(|row| row_difference(&before[row + dy as usize], &after[row]) as u64)
+            .sum();
+        let error = total as f32 / (overlap as f32 * columns);
+        if best.is_none_or(|(_, best_error)| error < best_error) {
+            best = Some((dy, error));
+        }
+    }
+
+    best.filter(|(_, error)| *error <= MATCH_THRESHOLD).map(|(dy, _)| dy)
+}
+
+// Result of adding a frame
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StitchResult {
+    Appended(u32), // Number of new rows
+    Unchanged,     // Not scrolled (or scrolled up)
+    NoOverlap,     // Scrolled too far between two frames, frame ignored
+    Full,          // MAX_HEIGHT reached
+}
+
+pub struct Stitcher {
+    image: RgbaImage,
+    last: RgbaImage,
+    frames: u32,
+}
+
+impl Stitcher {
+    pub fn new(first: RgbaImage) -> Self {
+        Self { image: first.clone(), last: first, frames: 1 }
+    }
+
+    pub fn push(&mut self, frame: RgbaImage) -> StitchResult {
+        let Some(dy) = find_vertical_offset(&self.last, &frame) else {
+            return StitchResult::NoOverlap;
+        };
+        if dy == 0 {
+            return StitchResult::Unchanged;
+        }
+        if self.image.height() + dy > MAX_HEIGHT {
+            return StitchResult::Full;
+        }
+
+        // The last `dy` rows of the frame are new
+        let (width, height) = frame.dimensions();
+        let mut grown = RgbaImage::new(width, self.image.height() + dy);
+        image::imageops::replace(&mut grown, &self.image, 0, 0);
+        let new_rows = image::imageops::crop_imm(&frame, 0, height - dy, width, dy).to_image();
+        image::imageops::replace(&mut grown, &new_rows, 0, self.image.height() as i64);
+
+        self.image = grown;
+        self.last = frame;
+        self.frames += 1;
+        StitchResult::Appended(dy)
+    }
+
+    pub fn height(&self) -> u32 {
+        self.image.height()
+    }
+
+    pub fn frames(&self) -> u32 {
+        self.frames
+    }
+
+    pub fn into_image(self) -> RgbaImage {
+        self.image
+    }
+}
+
+// Running session, kept in AppState
+pub struct ScrollSession {
+    stop: Arc<AtomicBool>,
+    scale_factor: f32,
+    stitcher: Arc<Mutex<Stitcher>>,
+    thread: JoinHandle<()>,
+    hidden: Vec<WebviewWindow>, // Shown again when the session ends
+}
+
+// Event payload sent after each captured frame
+#[derive(Clone, Serialize)]
+pub struct ScrollProgress {
+    pub frames: u32,
+    pub height: u32,
+    pub status: String, // "appended", "unchanged", "no-overlap" or "full"
+}
+
+#[derive(Clone, Serialize)]
+pub struct ScrollResult {
+    pub frames: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+// Start capturing the region (logical pixels of the display of the current capture)
+pub fn start(app: &AppHandle, bounds: SelectionBounds) -> Result<(), String> {
+    let state: State<'_, AppState> = app.state();
+    let mut session = state.scroll_capture.lock().unwrap();
+    if session.is_some() {
+        return Err("A scrolling capture is already running".to_string());
+    }
+    let display_id = *state.current_display.lock().unwrap();
+    let scale_factor = capture::find_display(state.capture_source.as_ref(), display_id)?.scale_factor;
+
+    let hidden = crate::hide_own_windows(app);
+    let first = match capture::capture_region(state.capture_source.as_ref(), display_id, Some(&bounds), NO_CURSOR) {
+        Ok(first) => first,
+        Err(e) => {
+            crate::restore_windows(hidden);
+            return Err(e);
+        }
+    };
+    let stitcher = Arc::new(Mutex::new(Stitcher::new(first)));
+    let stop = Arc::new(AtomicBool::new(false));
+
+    println!(
+        "[LOG] {} Scrolling capture started: {:?} on display {:?}",
+        Local::now().format("%H:%M:%S%.3f"),
+        bounds,
+        display_id
+    );
+
+    let app_handle = app.clone();
+    let thread_stop = stop.clone();
+    let thread_stitcher = stitcher.clone();
+    let thread = std::thread::spawn(move || {
+        let state: State<'_, AppState> = app_handle.state();
+        while !thread_stop.load(Ordering::SeqCst) {
+            std::thread::sleep(POLL_INTERVAL);
+            let frame = match capture::capture_region(state.capture_source.as_ref(), display_id, Some(&bounds), NO_CURSOR) {
+                Ok(frame) => frame,
+                Err(e) => {
+                    println!("[LOG] {} Scrolling capture failed: {}", Local::now().format("%H:%M:%S%.3f"), e);
+                    continue;
+                }
+            };
+
+            let mut stitcher = thread_stitcher.lock().unwrap();
+            let result = stitcher.push(frame);
+            let status = match result {
+                StitchResult::Appended(_) => "appended",
+                StitchResult::Unchanged => "unchanged",
+                StitchResult::NoOverlap => "no-overlap",
+                StitchResult::Full => "full",
+            };
+            if result != StitchResult::Unchanged {
+                let _ = app_handle.emit("scroll-capture-progress", ScrollProgress {
+                    frames: stitcher.frames(),
+                    height: stitcher.height(),
+                    status: status.to_string(),
+                });
+            }
+            if result == StitchResult::Full {
+                break;
+            }
+        }
+    });
+
+    *session = Some(ScrollSession { stop, scale_factor, stitcher, thread, hidden });
+    set_tray_tooltip(app, "Smart PrtScr - Capture avec défilement (Terminer depuis ce menu)");
+    Ok(())
+}
+
+// Stop the session and make the stitched image the pending screenshot
+pub fn finish(app: &AppHandle) -> Result<ScrollResult, String> {
+    let state: State<'_, AppState> = app.state();
+    let session = state.scroll_capture.lock().unwrap().take().ok_or("No scrolling capture running")?;
+    session.stop.store(true, Ordering::SeqCst);
+    let _ = session.thread.join();
+    crate::restore_windows(session.hidden);
+    set_tray_tooltip(app, TRAY_TOOLTIP);
+
+    let stitcher = Arc::try_unwrap(session.stitcher)
+        .map_err(|_| "Scrolling capture is still in use".to_string())?
+        .into_inner()
+        .unwrap();
+    let frames = stitcher.frames();
+    let img = stitcher.into_image();
+    let (width, height) = img.dimensions();
+
+    let mut buffer = Cursor::new(Vec::new());
+    DynamicImage::ImageRgba8(img)
+        .write_to(&mut buffer, ImageFormat::Png)
+        .map_err(|e| e.to_string())?;
+
+    let mut pending = state.pending_screenshot.lock().unwrap();
+    *pending = Some(PendingScreenshot {
+        image_data: buffer.into_inner(),
+        default_filename: crate::generate_default_filename(),
+        scale_factor: session.scale_factor,
+        context: None,
+    });
+
+    println!(
+        "[LOG] {} Scrolling capture finished: {} frames, {}x{}",
+        Local::now().format("%H:%M:%S%.3f"),
+        frames,
+        width,
+        height
+    );
+    let result = ScrollResult { frames, width, height };
+    // The dialog may not be the caller (tray menu)
+    let _ = app.emit("scroll-capture-finished", result.clone());
+    Ok(result)
+}
+
+// Stop the session without keeping the result
+pub fn abort(app: &AppHandle, state: &AppState) {
+    let session = state.scroll_capture.lock().unwrap().take();
+    if let Some(session) = session {
+        session.stop.store(true, Ordering::SeqCst);
+        let _ = session.thread.join();
+        crate::restore_windows(session.hidden);
+        set_tray_tooltip(app, TRAY_TOOLTIP);
+    }
+}
+
+fn set_tray_tooltip(app: &AppHandle, tooltip: &str) {
+    if let Some(tray) = app.tray_by_id(TRAY_ID) {
+        let _ = tray.set_tooltip(Some(tooltip));
+    }
+}
+
+// Tray menu entry: finish the session off the event loop (joins the capture thread)
+pub fn finish_from_tray(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Err(e) = finish(&app) {
+            println!("[LOG] {} Cannot finish scrolling capture: {}", Local::now().format("%H:%M:%S%.3f"), e);
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn start_scroll_capture(app: AppHandle, bounds: SelectionBounds) -> Result<(), String> {
+    // Hiding our windows waits for a repaint: not on an async worker
+    tauri::async_runtime::spawn_blocking(move || start(&app, bounds))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+pub async fn stop_scroll_capture(app: AppHandle) -> Result<ScrollResult, String> {
+    finish(&app)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    const WIDTH: u32 = 64;
+    const FRAME_HEIGHT: u32 = 100;
+
+    // Scrollable content: high-entropy rows (xorshift noise), so every offset is unambiguous
+    fn page(height: u32) -> RgbaImage {
+        let mut state = 7u32;
+        RgbaImage::from_fn(WIDTH, height, |_, _| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            Rgba([state as u8, (state >> 8) as u8, (state >> 16) as u8, 255])
+        })
+    }
+
+    // What the region shows when the page is scrolled by `scroll` rows
+    fn frame(page: &RgbaImage, scroll: u32) -> RgbaImage {
+        image::imageops::crop_imm(page, 0, scroll, WIDTH, FRAME_HEIGHT).to_image()
+    }
+
+    #[test]
+    fn offset_of_a_scrolled_frame_is_found() {
+        let page = page(400);
+        assert_eq!(find_vertical_offset(&frame(&page, 0), &frame(&page, 0)), Some(0));
+        assert_eq!(find_vertical_offset(&frame(&page, 0), &frame(&page, 37)), Some(37));
+        assert_eq!(find_vertical_offset(&frame(&page, 120), &frame(&page, 200)), Some(80));
+    }
+
+    #[test]
+    fn frames_without_overlap_have_no_offset() {
+        let page = page(400);
+        assert_eq!(find_vertical_offset(&frame(&page, 0), &frame(&page, 200)), None);
+        // Less than MIN_OVERLAP_RATIO of the frame in common
+        assert_eq!(find_vertical_offset(&frame(&page, 0), &frame(&page, 90)), None);
+    }
+
+    #[test]
+    fn stitched_image_is_the_scrolled_page() {
+        let page = page(400);
+        let mut stitcher = Stitcher::new(frame(&page, 0));
+        assert_eq!(stitcher.push(frame(&page, 0)), StitchResult::Unchanged);
+        assert_eq!(stitcher.push(frame(&page, 40)), StitchResult::Appended(40));
+        assert_eq!(stitcher.push(frame(&page, 300)), StitchResult::NoOverlap);
+        assert_eq!(stitcher.push(frame(&page, 110)), StitchResult::Appended(70));
+        assert_eq!(stitcher.frames(), 3);
+        assert_eq!(stitcher.into_image(), image::imageops::crop_imm(&page, 0, 0, WIDTH, 210).to_image());
+    }
+
+    #[test]
+    fn stitcher_stops_at_max_height() {
+        let page = page(400);
+        // As if many frames had already been stitched
+        let mut stitcher = Stitcher {
+            image: RgbaImage::new(WIDTH, MAX_HEIGHT - 10),
+            last: frame(&page, 0),
+            frames: 1,
+        };
+        assert_eq!(stitcher.push(frame(&page, 20)), StitchResult::Full);
+        assert_eq!(stitcher.height(), MAX_HEIGHT - 10);
+        assert_eq!(stitcher.push(frame(&page, 10)), StitchResult::Appended(10));
+        assert_eq!(stitcher.height(), MAX_HEIGHT);
+        assert_eq!(stitcher.push(frame(&page, 11)), StitchResult::Full);
+    }
+}
@@ -0,0 +1,172 @@
+// Selection coordinate space.
+//
+// `SelectionBounds` sent by the selection window are logical (CSS) pixels relative to
+// the top-left corner of the captured display, which is where the fullscreen selection
+// window sits. The frozen frame is stored in physical pixels, so bounds are scaled by
+// the display scale factor, then clamped to the frame before cropping.
+
+use crate::SelectionBounds;
+use std::fmt;
+
+// Selection rectangle in physical pixels, guaranteed to lie inside the frame
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PixelRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SelectionError {
+    // Width or height is zero or negative
+    EmptySelection { width: i32, height: i32 },
+    // The selection does not intersect the captured frame
+    OutsideFrame,
+    // Scale factor is zero, negative or not a number
+    InvalidScaleFactor(f32),
+}
+
+impl fmt::Display for SelectionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SelectionError::EmptySelection { width, height } => {
+                write!(f, "Selection is empty ({}x{})", width, height)
+            }
+            SelectionError::OutsideFrame => write!(f, "Selection is outside of the captured screen"),
+            SelectionError::InvalidScaleFactor(scale) => write!(f, "Invalid display scale factor: {}", scale),
+        }
+    }
+}
+
+impl std::error::Error for SelectionError {}
+
+// Convert logical selection bounds to a physical pixel rectangle clamped to the frame.
+// Partially visible selections are clipped; edges are rounded outwards so the
+// physical rectangle always covers the whole logical selection.
+pub fn to_physical_rect(
+    bounds: &SelectionBounds,
+    scale_factor: f32,
+    frame_width: u32,
+    frame_height: u32,
+) -> Result<PixelRect, SelectionError> {
+    if !scale_factor.is_finite() || scale_factor <= 0.0 {
+        return Err(SelectionError::InvalidScaleFactor(scale_factor));
+    }
+    if bounds.width <= 0 || bounds.height <= 0 {
+        return Err(SelectionError::EmptySelection {
+            width: bounds.width,
+            height: bounds.height,
+        });
+    }
+
+    let scale = scale_factor as f64;
+    let left = (bounds.x as f64 * scale).floor();
+    let top = (bounds.y as f64 * scale).floor();
+    let right = ((bounds.x as f64 + bounds.width as f64) * scale).ceil();
+    let bottom = ((bounds.y as f64 + bounds.height as f64) * scale).ceil();
+
+    let left = left.clamp(0.0, frame_width as f64) as u32;
+    let top = top.clamp(0.0, frame_height as f64) as u32;
+    let right = right.clamp(0.0, frame_width as f64) as u32;
+    let bottom = bottom.clamp(0.0, frame_height as f64) as u32;
+
+    if right <= left || bottom <= top {
+        return Err(SelectionError::OutsideFrame);
+    }
+
+    Ok(PixelRect {
+        x: left,
+        y: top,
+        width: right - left,
+        height: bottom - top,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bounds(x: i32, y: i32, width: i32, height: i32) -> SelectionBounds {
+        SelectionBounds { x, y, width, height }
+    }
+
+    fn rect(x: u32, y: u32, width: u32, height: u32) -> PixelRect {
+        PixelRect { x, y, width, height }
+    }
+
+    #[test]
+    fn inside_frame_is_scaled() {
+        assert_eq!(to_physical_rect(&bounds(10, 20, 30, 40), 1.0, 100, 100), Ok(rect(10, 20, 30, 40)));
+        assert_eq!(to_physical_rect(&bounds(10, 20, 30, 40), 2.0, 200, 200), Ok(rect(20, 40, 60, 80)));
+    }
+
+    #[test]
+    fn negative_origin_is_clipped() {
+        assert_eq!(to_physical_rect(&bounds(-10, -5, 30, 20), 1.0, 100, 100), Ok(rect(0, 0, 20, 15)));
+        assert_eq!(to_physical_rect(&bounds(-10, -5, 30, 20), 2.0, 200, 200), Ok(rect(0, 0, 40, 30)));
+    }
+
+    #[test]
+    fn right_and_bottom_edges_are_clipped() {
+        assert_eq!(to_physical_rect(&bounds(90, 95, 20, 20), 1.0, 100, 100), Ok(rect(90, 95, 10, 5)));
+        assert_eq!(to_physical_rect(&bounds(0, 0, 500, 500), 1.0, 100, 80), Ok(rect(0, 0, 100, 80)));
+    }
+
+    #[test]
+    fn zero_and_negative_sizes_are_empty() {
+        assert_eq!(
+            to_physical_rect(&bounds(10, 10, 0, 10), 1.0, 100, 100),
+            Err(SelectionError::EmptySelection { width: 0, height: 10 })
+        );
+        assert_eq!(
+            to_physical_rect(&bounds(10, 10, 10, 0), 1.0, 100, 100),
+            Err(SelectionError::EmptySelection { width: 10, height: 0 })
+        );
+        assert_eq!(
+            to_physical_rect(&bounds(10, 10, -5, 10), 1.0, 100, 100),
+            Err(SelectionError::EmptySelection { width: -5, height: 10 })
+        );
+        assert_eq!(
+            to_physical_rect(&bounds(10, 10, 10, -5), 1.0, 100, 100),
+            Err(SelectionError::EmptySelection { width: 10, height: -5 })
+        );
+    }
+
+    #[test]
+    fn selection_outside_frame_is_rejected() {
+        assert_eq!(to_physical_rect(&bounds(150, 10, 20, 20), 1.0, 100, 100), Err(SelectionError::OutsideFrame));
+        assert_eq!(to_physical_rect(&bounds(10, 100, 20, 20), 1.0, 100, 100), Err(SelectionError::OutsideFrame));
+        assert_eq!(to_physical_rect(&bounds(-50, -50, 20, 20), 1.0, 100, 100), Err(SelectionError::OutsideFrame));
+        // Touching the right edge from outside is still outside
+        assert_eq!(to_physical_rect(&bounds(50, 0, 10, 10), 2.0, 100, 100), Err(SelectionError::OutsideFrame));
+    }
+
+    #[test]
+    fn fractional_scales_round_outwards() {
+        // 1.25: left 3.75 -> 3, right 10.0 -> 10
+        assert_eq!(to_physical_rect(&bounds(3, 3, 5, 5), 1.25, 1000, 1000), Ok(rect(3, 3, 7, 7)));
+        // 1.25: left 1.25 -> 1, right 6.25 -> 7
+        assert_eq!(to_physical_rect(&bounds(1, 1, 4, 4), 1.25, 1000, 1000), Ok(rect(1, 1, 6, 6)));
+        // 1.5: left 4.5 -> 4, top 7.5 -> 7, right 15.0, bottom 21.0
+        assert_eq!(to_physical_rect(&bounds(3, 5, 7, 9), 1.5, 1000, 1000), Ok(rect(4, 7, 11, 14)));
+        // 1.5: one logical pixel at an odd position spans parts of 2 physical pixels
+        assert_eq!(to_physical_rect(&bounds(1, 1, 1, 1), 1.5, 1000, 1000), Ok(rect(1, 1, 2, 2)));
+        // Rounded edges are still clamped to the frame
+        assert_eq!(to_physical_rect(&bounds(1, 1, 7, 7), 1.5, 11, 11), Ok(rect(1, 1, 10, 10)));
+    }
+
+    #[test]
+    fn invalid_scale_factors_are_rejected() {
+        assert_eq!(to_physical_rect(&bounds(0, 0, 10, 10), 0.0, 100, 100), Err(SelectionError::InvalidScaleFactor(0.0)));
+        assert_eq!(to_physical_rect(&bounds(0, 0, 10, 10), -1.0, 100, 100), Err(SelectionError::InvalidScaleFactor(-1.0)));
+        assert!(matches!(
+            to_physical_rect(&bounds(0, 0, 10, 10), f32::NAN, 100, 100),
+            Err(SelectionError::InvalidScaleFactor(scale)) if scale.is_nan()
+        ));
+        assert!(matches!(
+            to_physical_rect(&bounds(0, 0, 10, 10), f32::INFINITY, 100, 100),
+            Err(SelectionError::InvalidScaleFactor(_))
+        ));
+    }
+}
@@ -0,0 +1,241 @@
+// Time-lapse capture: a fixed region captured every N seconds with the timestamp
+// burned in, written to an output folder by a background thread.
+
+use crate::background::{self, SessionHandle};
+use crate::{add_timestamp_to_rgba, capture, cursor, resolve_output_options, AppState, SelectionBounds, TimestampOptions};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+// settings.json key of the last started configuration
+const CONFIG_KEY: &str = "timelapseConfig";
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct TimelapseConfig {
+    #[serde(rename = "displayId", default)]
+    pub display_id: Option<u32>, // None = primary display
+    #[serde(default)]
+    pub region: Option<SelectionBounds>, // None = whole display
+    #[serde(rename = "intervalSecs")]
+    pub interval_secs: u32,
+    #[serde(rename = "durationSecs", default)]
+    pub duration_secs: Option<u32>, // Stop after this duration...
+    #[serde(default)]
+    pub count: Option<u32>, // ...or this number of captures (unlimited if both are None)
+    #[serde(rename = "outputFolder", default)]
+    pub output_folder: Option<String>, // None = new folder in the save path
+    #[serde(rename = "timestampOptions", default)]
+    pub timestamp_options: Option<TimestampOptions>, // None = stored options
+    #[serde(rename = "imageFormat", default)]
+    pub image_format: Option<String>, // None = stored format
+}
+
+// Event payload sent after each capture
+#[derive(Clone, Serialize)]
+pub struct TimelapseProgress {
+    pub taken: u32,
+    pub total: Option<u32>,
+    #[serde(rename = "elapsedSecs")]
+    pub elapsed_secs: u64,
+    #[serde(rename = "lastPath")]
+    pub last_path: Option<String>,
+    pub error: Option<String>,
+}
+
+// Event payload sent when the session ends ("completed" or "stopped")
+#[derive(Clone, Serialize)]
+pub struct TimelapseFinished {
+    pub taken: u32,
+    #[serde(rename = "outputFolder")]
+    pub output_folder: String,
+    pub reason: String,
+}
+
+#[derive(Clone, Serialize)]
+pub struct TimelapseStatus {
+    pub running: bool,
+    pub taken: u32,
+    #[serde(rename = "outputFolder")]
+    pub output_folder: Option<String>,
+    pub config: Option<TimelapseConfig>,
+}
+
+fn validate(config: &TimelapseConfig) -> Result<(), String> {
+    if config.interval_secs == 0 {
+        return Err("Interval must be at least 1 second".to_string());
+    }
+    if config.count == Some(0) || config.duration_secs == Some(0) {
+        return Err("Count and duration must be greater than 0".to_string());
+    }
+    if let Some(region) = &config.region {
+        if region.width <= 0 || region.height <= 0 {
+            return Err(format!("Region is empty ({}x{})", region.width, region.height));
+        }
+    }
+    Ok(())
+}
+
+// Start a session (stored configuration if none is given); fails if one is already running
+pub fn start(app: &AppHandle, config: Option<TimelapseConfig>) -> Result<TimelapseStatus, String> {
+    let config = match config {
+        Some(config) => config,
+        None => background::read_stored_config(app, CONFIG_KEY).ok_or("No time-lapse configured")?,
+    };
+    validate(&config)?;
+
+    let state: State<'_, AppState> = app.state();
+    if state.timelapse.lock().unwrap().is_some() {
+        return Err("A time-lapse is already running".to_string());
+    }
+    let output_folder = background::output_folder(app, &config.output_folder, "timelapse")?;
+    let (timestamp_options, image_format) = resolve_output_options(app, &config.timestamp_options, &config.image_format)?;
+
+    // Remember the configuration for the tray entry
+    background::store_config(app, CONFIG_KEY, &config)?;
+
+    let session = background::claim(
+        &state.timelapse,
+        output_folder.clone(),
+        config.clone(),
+        "A time-lapse is already running",
+    )?;
+
+    println!(
+        "[LOG] {} Time-lapse started: every {} s into {}",
+        Local::now().format("%H:%M:%S%.3f"),
+        config.interval_secs,
+        output_folder.display()
+    );
+
+    let app_handle = app.clone();
+    let folder = output_folder.clone();
+    let thread_config = config.clone();
+    std::thread::spawn(move || {
+        run(&app_handle, &thread_config, &folder, &timestamp_options, &image_format, &session);
+    });
+
+    Ok(TimelapseStatus {
+        running: true,
+        taken: 0,
+        output_folder: Some(output_folder.to_string_lossy().to_string()),
+        config: Some(config),
+    })
+}
+
+fn run(
+    app: &AppHandle,
+    config: &TimelapseConfig,
+    output_folder: &Path,
+    timestamp_options: &TimestampOptions,
+    image_format: &str,
+    session: &SessionHandle,
+) {
+    let state: State<'_, AppState> = app.state();
+    let extension = if image_format == "png" { "png" } else { "jpg" };
+    let interval = Duration::from_secs(config.interval_secs as u64);
+    let deadline = config.duration_secs.map(|secs| Duration::from_secs(secs as u64));
+    let cursor = cursor::read_cursor_options(app);
+    let started = Instant::now();
+    let mut index: u32 = 0;
+
+    let reason = loop {
+        if session.stop_requested() {
+            break "stopped";
+        }
+
+        // Errors are reported but do not end the session (long unattended runs)
+        let result = capture::capture_region(state.capture_source.as_ref(), config.display_id, config.region.as_ref(), cursor)
+            .and_then(|img| add_timestamp_to_rgba(img, timestamp_options, image_format))
+            .and_then(|processed| {
+                let filename = format!(
+                    "{:05}_{}.{}",
+                    index + 1,
+                    Local::now().format("%Y-%m-%dT%H-%M-%S"),
+                    extension
+                );
+                let path = output_folder.join(filename);
+                fs::write(&path, &processed.encoded_bytes).map_err(|e| e.to_string())?;
+                Ok(path)
+            });
+
+        index += 1;
+        let (last_path, error) = match result {
+            Ok(path) => {
+                session.add_capture();
+                (Some(path.to_string_lossy().to_string()), None)
+            }
+            Err(e) => {
+                println!("[LOG] {} Time-lapse capture failed: {}", Local::now().format("%H:%M:%S%.3f"), e);
+                (None, Some(e))
+            }
+        };
+        let _ = app.emit("timelapse-progress", TimelapseProgress {
+            taken: session.captures(),
+            total: config.count,
+            elapsed_secs: started.elapsed().as_secs(),
+            last_path,
+            error,
+        });
+
+        if config.count.is_some_and(|count| index >= count) {
+            break "completed";
+        }
+
+        // Wait for the next tick (scheduled from the start time, so no drift), checking for stop
+        let next_tick = interval * index;
+        if deadline.is_some_and(|deadline| next_tick > deadline) {
+            break "completed";
+        }
+        while started.elapsed() < next_tick && !session.stop_requested() {
+            std::thread::sleep(Duration::from_millis(100));
+        }
+    };
+
+    background::release(&state.timelapse, session);
+    let taken = session.captures();
+    println!("[LOG] {} Time-lapse {} after {} captures", Local::now().format("%H:%M:%S%.3f"), reason, taken);
+    let _ = app.emit("timelapse-finished", TimelapseFinished {
+        taken,
+        output_folder: output_folder.to_string_lossy().to_string(),
+        reason: reason.to_string(),
+    });
+}
+
+// Ask the running session to stop after the current capture
+pub fn stop(app: &AppHandle) {
+    let state: State<'_, AppState> = app.state();
+    background::request_stop(&state.timelapse);
+}
+
+#[tauri::command]
+pub async fn start_timelapse(app: AppHandle, config: Option<TimelapseConfig>) -> Result<TimelapseStatus, String> {
+    start(&app, config)
+}
+
+#[tauri::command]
+pub async fn stop_timelapse(app: AppHandle) -> Result<(), String> {
+    stop(&app);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_timelapse_status(app: AppHandle) -> Result<TimelapseStatus, String> {
+    let state: State<'_, AppState> = app.state();
+    Ok(match background::info(&state.timelapse) {
+        Some(info) => TimelapseStatus {
+            running: true,
+            taken: info.captures,
+            output_folder: Some(info.output_folder),
+            config: Some(info.config),
+        },
+        None => TimelapseStatus {
+            running: false,
+            taken: 0,
+            output_folder: None,
+            config: background::read_stored_config(&app, CONFIG_KEY),
+        },
+    })
+}
@@ -0,0 +1,272 @@
+// Region watcher: polls a region and saves a stamped capture only when it changed
+// enough since the last saved capture (dashboards, monitoring screens).
+
+use crate::background::{self, SessionHandle};
+use crate::{add_timestamp_to_rgba, capture, cursor, image_diff, resolve_output_options, AppState, SelectionBounds, TimestampOptions};
+use chrono::Local;
+use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+// settings.json key of the last started configuration
+const CONFIG_KEY: &str = "regionWatchConfig";
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct WatchConfig {
+    #[serde(rename = "displayId", default)]
+    pub display_id: Option<u32>, // None = primary display
+    #[serde(default)]
+    pub region: Option<SelectionBounds>, // None = whole display
+    #[serde(rename = "pollIntervalMs", default = "default_poll_interval_ms")]
+    pub poll_interval_ms: u64,
+    // Fraction of changed pixels (0.0 - 1.0) above which a capture is saved
+    #[serde(default = "default_threshold")]
+    pub threshold: f32,
+    // Luma difference (0 - 255) under which a pixel is considered unchanged
+    #[serde(default = "default_tolerance")]
+    pub tolerance: u8,
+    // Minimum delay between two saved captures
+    #[serde(rename = "minIntervalSecs", default = "default_min_interval_secs")]
+    pub min_interval_secs: u64,
+    #[serde(rename = "outlineChanges", default)]
+    pub outline_changes: bool,
+    #[serde(rename = "outputFolder", default)]
+    pub output_folder: Option<String>, // None = new folder in the save path
+    #[serde(rename = "timestampOptions", default)]
+    pub timestamp_options: Option<TimestampOptions>, // None = stored options
+    #[serde(rename = "imageFormat", default)]
+    pub image_format: Option<String>, // None = stored format
+}
+
+fn default_poll_interval_ms() -> u64 {
+    1000
+}
+
+fn default_threshold() -> f32 {
+    0.01
+}
+
+fn default_tolerance() -> u8 {
+    24
+}
+
+fn default_min_interval_secs() -> u64 {
+    10
+}
+
+// Event payload sent for each saved capture
+#[derive(Clone, Serialize)]
+pub struct WatchCapturePayload {
+    pub saved: u32,
+    #[serde(rename = "changedRatio")]
+    pub changed_ratio: f32,
+    pub path: String,
+}
+
+#[derive(Clone, Serialize)]
+pub struct WatchStatus {
+    pub running: bool,
+    pub saved: u32,
+    #[serde(rename = "outputFolder")]
+    pub output_folder: Option<String>,
+    pub config: Option<WatchConfig>,
+}
+
+fn validate(config: &WatchConfig) -> Result<(), String> {
+    if config.poll_interval_ms < 100 {
+        return Err("Poll interval must be at least 100 ms".to_string());
+    }
+    if !(0.0..=1.0).contains(&config.threshold) {
+        return Err("Threshold must be between 0 and 1".to_string());
+    }
+    if let Some(region) = &config.region {
+        if region.width <= 0 || region.height <= 0 {
+            return Err(format!("Region is empty ({}x{})", region.width, region.height));
+        }
+    }
+    Ok(())
+}
+
+// Start watching (stored configuration if none is given); fails if already watching
+pub fn start(app: &AppHandle, config: Option<WatchConfig>) -> Result<WatchStatus, String> {
+    let config = match config {
+        Some(config) => config,
+        None => background::read_stored_config(app, CONFIG_KEY).ok_or("No region watch configured")?,
+    };
+    validate(&config)?;
+
+    let state: State<'_, AppState> = app.state();
+    if state.watcher.lock().unwrap().is_some() {
+        return Err("A region watch is already running".to_string());
+    }
+    let output_folder = background::output_folder(app, &config.output_folder, "watch")?;
+    let (timestamp_options, image_format) = resolve_output_options(app, &config.timestamp_options, &config.image_format)?;
+
+    // Remember the configuration for the tray entry
+    background::store_config(app, CONFIG_KEY, &config)?;
+
+    let session = background::claim(
+        &state.watcher,
+        output_folder.clone(),
+        config.clone(),
+        "A region watch is already running",
+    )?;
+
+    println!(
+        "[LOG] {} Region watch started (threshold {:.1}%) into {}",
+        Local::now().format("%H:%M:%S%.3f"),
+        config.threshold * 100.0,
+        output_folder.display()
+    );
+
+    let app_handle = app.clone();
+    let folder = output_folder.clone();
+    let thread_config = config.clone();
+    std::thread::spawn(move || {
+        run(&app_handle, &thread_config, &folder, &timestamp_options, &image_format, &session);
+    });
+
+    Ok(WatchStatus {
+        running: true,
+        saved: 0,
+        output_folder: Some(output_folder.to_string_lossy().to_string()),
+        config: Some(config),
+    })
+}
+
+fn save_frame(
+    frame: &RgbaImage,
+    regions: &[imageproc::rect::Rect],
+    config: &WatchConfig,
+    output_folder: &Path,
+    timestamp_options: &TimestampOptions,
+    image_format: &str,
+) -> Result<PathBuf, String> {
+    let mut img = frame.clone();
+    if config.outline_changes {
+        image_diff::outline_regions(&mut img, regions);
+    }
+    let processed = add_timestamp_to_rgba(img, timestamp_options, image_format)?;
+    let extension = if image_format == "png" { "png" } else { "jpg" };
+    let filename = format!("{}_watch.{}", Local::now().format("%Y-%m-%dT%H-%M-%S"), extension);
+    let path = output_folder.join(filename);
+    fs::write(&path, &processed.encoded_bytes).map_err(|e| e.to_string())?;
+    Ok(path)
+}
+
+fn run(
+    app: &AppHandle,
+    config: &WatchConfig,
+    output_folder: &Path,
+    timestamp_options: &TimestampOptions,
+    image_format: &str,
+    session: &SessionHandle,
+) {
+    let state: State<'_, AppState> = app.state();
+    let poll_interval = Duration::from_millis(config.poll_interval_ms);
+    let min_interval = Duration::from_secs(config.min_interval_secs);
+    // Last saved frame: changes are measured against it, so slow changes add up
+    let mut reference: Option<RgbaImage> = None;
+    let mut last_saved: Option<Instant> = None;
+    let cursor = cursor::read_cursor_options(app);
+
+    while !session.stop_requested() {
+        let polled = Instant::now();
+        // Changes are measured without the cursor (moving the mouse is not a change), the
+        // saved frame gets it when the options ask for it
+        let captured = capture::capture_region_raw(state.capture_source.as_ref(), config.display_id, config.region.as_ref())
+            .and_then(|raw| {
+                let shown = if cursor.include && raw.cursor.is_some() {
+                    let mut shown = raw.clone();
+                    cursor::composite(&mut shown, cursor.halo);
+                    Some(capture::into_image(shown)?)
+                } else {
+                    None
+                };
+                Ok((capture::into_image(raw)?, shown))
+            });
+        match captured {
+            Ok((frame, shown)) => {
+                let diff = reference.as_ref().map(|previous| image_diff::compare(previous, &frame, config.tolerance));
+                let changed = diff.as_ref().is_none_or(|d| d.changed_ratio > config.threshold);
+                let allowed = last_saved.is_none_or(|t| t.elapsed() >= min_interval);
+
+                // A change seen too early is kept until the minimum interval has elapsed
+                if changed && allowed {
+                    let (ratio, regions) = match diff {
+                        Some(d) => (d.changed_ratio, d.regions),
+                        None => (0.0, Vec::new()),
+                    };
+                    match save_frame(shown.as_ref().unwrap_or(&frame), &regions, config, output_folder, timestamp_options, image_format) {
+                        Ok(path) => {
+                            let count = session.add_capture();
+                            println!(
+                                "[LOG] {} Region changed ({:.1}%), saved {}",
+                                Local::now().format("%H:%M:%S%.3f"),
+                                ratio * 100.0,
+                                path.display()
+                            );
+                            let _ = app.emit("region-watch-capture", WatchCapturePayload {
+                                saved: count,
+                                changed_ratio: ratio,
+                                path: path.to_string_lossy().to_string(),
+                            });
+                        }
+                        Err(e) => println!("[LOG] {} Region watch save failed: {}", Local::now().format("%H:%M:%S%.3f"), e),
+                    }
+                    reference = Some(frame);
+                    last_saved = Some(Instant::now());
+                }
+            }
+            Err(e) => println!("[LOG] {} Region watch capture failed: {}", Local::now().format("%H:%M:%S%.3f"), e),
+        }
+
+        while polled.elapsed() < poll_interval && !session.stop_requested() {
+            std::thread::sleep(Duration::from_millis(50));
+        }
+    }
+
+    background::release(&state.watcher, session);
+    let saved = session.captures();
+    println!("[LOG] {} Region watch stopped after {} captures", Local::now().format("%H:%M:%S%.3f"), saved);
+    let _ = app.emit("region-watch-stopped", saved);
+}
+
+// Ask the running watch to stop after the current poll
+pub fn stop(app: &AppHandle) {
+    let state: State<'_, AppState> = app.state();
+    background::request_stop(&state.watcher);
+}
+
+#[tauri::command]
+pub async fn start_region_watch(app: AppHandle, config: Option<WatchConfig>) -> Result<WatchStatus, String> {
+    start(&app, config)
+}
+
+#[tauri::command]
+pub async fn stop_region_watch(app: AppHandle) -> Result<(), String> {
+    stop(&app);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_region_watch_status(app: AppHandle) -> Result<WatchStatus, String> {
+    let state: State<'_, AppState> = app.state();
+    Ok(match background::info(&state.watcher) {
+        Some(info) => WatchStatus {
+            running: true,
+            saved: info.captures,
+            output_folder: Some(info.output_folder),
+            config: Some(info.config),
+        },
+        None => WatchStatus {
+            running: false,
+            saved: 0,
+            output_folder: None,
+            config: background::read_stored_config(&app, CONFIG_KEY),
+        },
+    })
+}
@@ -0,0 +1,332 @@
+// Window capture: enumeration of the visible top-level windows (title, process, bounds),
+// offered to the selection window as snapping targets.
+//
+// Linux goes through X11 (EWMH client list when a window manager runs, plain children
+// of the root window otherwise, e.g. under Xvfb); Windows through EnumWindows.
+
+use crate::capture::DisplayInfo;
+use crate::selection::PixelRect;
+use crate::SelectionBounds;
+use serde::Serialize;
+
+// Top-level window, bounds in screen pixels (decorations included)
+#[derive(Clone, Serialize, Debug)]
+pub struct WindowInfo {
+    pub id: u64,
+    pub title: String,
+    pub process: String,
+    pub pid: Option<u32>,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+// Window as seen by the selection window: bounds in its coordinate space
+// (logical pixels relative to the display), clipped to the frozen frame.
+// `rect` keeps the exact physical pixels, rounding to logical pixels can be off by one
+// at fractional scale factors.
+#[derive(Clone, Serialize, Debug)]
+pub struct SnapTarget {
+    pub id: u64,
+    pub title: String,
+    pub process: String,
+    pub bounds: SelectionBounds,
+    #[serde(skip)]
+    pub rect: PixelRect,
+}
+
+// Visible top-level windows, topmost first, without our own windows
+pub fn list_windows() -> Result<Vec<WindowInfo>, String> {
+    let own_pid = std::process::id();
+    let windows = platform::list_windows()?;
+    Ok(windows
+        .into_iter()
+        .filter(|w| w.pid != Some(own_pid) && w.width > 0 && w.height > 0)
+        .collect())
+}
+
+// Windows overlapping the frozen frame of a display (`frame_width` x `frame_height` physical
+// pixels), converted to its selection coordinates
+pub fn snap_targets(windows: &[WindowInfo], display: &DisplayInfo, frame_width: u32, frame_height: u32) -> Vec<SnapTarget> {
+    let scale = if display.scale_factor > 0.0 { display.scale_factor } else { 1.0 };
+
+    windows
+        .iter()
+        .filter_map(|w| {
+            // Clip to the frame (physical pixels relative to the display)
+            let left = (w.x - display.x).max(0);
+            let top = (w.y - display.y).max(0);
+            let right = (w.x - display.x + w.width as i32).min(frame_width as i32);
+            let bottom = (w.y - display.y + w.height as i32).min(frame_height as i32);
+            if right <= left || bottom <= top {
+                return None;
+            }
+            Some(SnapTarget {
+                id: w.id,
+                title: w.title.clone(),
+                process: w.process.clone(),
+                bounds: SelectionBounds {
+                    x: (left as f32 / scale).round() as i32,
+                    y: (top as f32 / scale).round() as i32,
+                    width: ((right - left) as f32 / scale).round() as i32,
+                    height: ((bottom - top) as f32 / scale).round() as i32,
+                },
+                rect: PixelRect {
+                    x: left as u32,
+                    y: top as u32,
+                    width: (right - left) as u32,
+                    height: (bottom - top) as u32,
+                },
+            })
+        })
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::WindowInfo;
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{Atom, AtomEnum, ConnectionExt, MapState, Window};
+    use x11rb::rust_connection::RustConnection;
+
+    fn x11_error(e: impl std::fmt::Display) -> String {
+        format!("X11: {}", e)
+    }
+
+    fn atom(conn: &RustConnection, name: &str) -> Result<Atom, String> {
+        Ok(conn
+            .intern_atom(false, name.as_bytes())
+            .map_err(x11_error)?
+            .reply()
+            .map_err(x11_error)?
+            .atom)
+    }
+
+    fn property(conn: &RustConnection, window: Window, property: Atom) -> Option<x11rb::protocol::xproto::GetPropertyReply> {
+        conn.get_property(false, window, property, AtomEnum::ANY, 0, u32::MAX / 4)
+            .ok()?
+            .reply()
+            .ok()
+            .filter(|reply| reply.value_len > 0)
+    }
+
+    fn process_name(pid: u32) -> String {
+        std::fs::read_to_string(format!("/proc/{}/comm", pid))
+            .map(|name| name.trim().to_string())
+            .unwrap_or_default()
+    }
+
+    pub fn list_windows() -> Result<Vec<WindowInfo>, String> {
+        let (conn, screen_num) = x11rb::connect(None).map_err(x11_error)?;
+        let root = conn.setup().roots[screen_num].root;
+
+        let net_client_list = atom(&conn, "_NET_CLIENT_LIST_STACKING")?;
+        let net_wm_name = atom(&conn, "_NET_WM_NAME")?;
+        let net_wm_pid = atom(&conn, "_NET_WM_PID")?;
+        let net_frame_extents = atom(&conn, "_NET_FRAME_EXTENTS")?;
+
+        // Bottom-to-top stacking order in both cases
+        let managed = property(&conn, root, net_client_list)
+            .and_then(|reply| reply.value32().map(|ids| ids.collect::<Vec<Window>>()));
+        let candidates = match managed {
+            Some(ids) => ids,
+            None => conn.query_tree(root).map_err(x11_error)?.reply().map_err(x11_error)?.children,
+        };
+
+        let mut windows = Vec::new();
+        for window in candidates.into_iter().rev() {
+            let Ok(attributes) = conn.get_window_attributes(window).map_err(x11_error)?.reply() else {
+                continue;
+            };
+            if attributes.map_state != MapState::VIEWABLE || attributes.override_redirect {
+                continue;
+            }
+            let Ok(geometry) = conn.get_geometry(window).map_err(x11_error)?.reply() else {
+                continue;
+            };
+            let Ok(origin) = conn.translate_coordinates(window, root, 0, 0).map_err(x11_error)?.reply() else {
+                continue;
+            };
+
+            // Include the decorations drawn by the window manager
+            let [left, right, top, bottom] = property(&conn, window, net_frame_extents)
+                .and_then(|reply| reply.value32().map(|v| v.collect::<Vec<u32>>()))
+                .and_then(|v| <[u32; 4]>::try_from(v).ok())
+                .unwrap_or([0; 4]);
+
+            let title = property(&conn, window, net_wm_name)
+                .or_else(|| property(&conn, window, AtomEnum::WM_NAME.into()))
+                .map(|reply| String::from_utf8_lossy(&reply.value).into_owned())
+                .unwrap_or_default();
+            let pid = property(&conn, window, net_wm_pid)
+                .and_then(|reply| reply.value32().and_then(|mut v| v.next()));
+
+            windows.push(WindowInfo {
+                id: window as u64,
+                title,
+                process: pid.map(process_name).unwrap_or_default(),
+                pid,
+                x: origin.dst_x as i32 - left as i32,
+                y: origin.dst_y as i32 - top as i32,
+                width: geometry.width as u32 + left + right,
+                height: geometry.height as u32 + top + bottom,
+            });
+        }
+        Ok(windows)
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::WindowInfo;
+    use windows::core::PWSTR;
+    use windows::Win32::Foundation::{CloseHandle, BOOL, HWND, LPARAM, RECT};
+    use windows::Win32::Graphics::Dwm::{DwmGetWindowAttribute, DWMWA_CLOAKED, DWMWA_EXTENDED_FRAME_BOUNDS};
+    use windows::Win32::System::Threading::{
+        OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::{
+        EnumWindows, GetWindowLongW, GetWindowTextW, GetWindowThreadProcessId, IsIconic, IsWindowVisible,
+        GWL_EXSTYLE, WS_EX_TOOLWINDOW,
+    };
+
+    fn process_name(pid: u32) -> String {
+        unsafe {
+            let Ok(handle) = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) else {
+                return String::new();
+            };
+            let mut buffer = [0u16; 260];
+            let mut size = buffer.len() as u32;
+            let result = QueryFullProcessImageNameW(handle, PROCESS_NAME_WIN32, PWSTR(buffer.as_mut_ptr()), &mut size);
+            let _ = CloseHandle(handle);
+            if result.is_err() {
+                return String::new();
+            }
+            let path = String::from_utf16_lossy(&buffer[..size as usize]);
+            path.rsplit('\\').next().unwrap_or_default().to_string()
+        }
+    }
+
+    // Called for each top-level window, in Z order (topmost first)
+    unsafe extern "system" fn enum_window(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        let windows = &mut *(lparam.0 as *mut Vec<WindowInfo>);
+
+        if !IsWindowVisible(hwnd).as_bool() || IsIconic(hwnd).as_bool() {
+            return BOOL(1);
+        }
+        if (GetWindowLongW(hwnd, GWL_EXSTYLE) as u32) & WS_EX_TOOLWINDOW.0 != 0 {
+            return BOOL(1);
+        }
+        // Windows hidden by the shell (other virtual desktops, suspended UWP apps)
+        let mut cloaked: u32 = 0;
+        let _ = DwmGetWindowAttribute(
+            hwnd,
+            DWMWA_CLOAKED,
+            &mut cloaked as *mut u32 as *mut _,
+            std::mem::size_of::<u32>() as u32,
+        );
+        if cloaked != 0 {
+            return BOOL(1);
+        }
+
+        let mut buffer = [0u16; 512];
+        let length = GetWindowTextW(hwnd, &mut buffer);
+        if length <= 0 {
+            return BOOL(1);
+        }
+        let title = String::from_utf16_lossy(&buffer[..length as usize]);
+
+        // Visible frame, without the invisible resize borders of GetWindowRect
+        let mut rect = RECT::default();
+        if DwmGetWindowAttribute(
+            hwnd,
+            DWMWA_EXTENDED_FRAME_BOUNDS,
+            &mut rect as *mut RECT as *mut _,
+            std::mem::size_of::<RECT>() as u32,
+        )
+        .is_err()
+        {
+            return BOOL(1);
+        }
+
+        let mut pid: u32 = 0;
+        GetWindowThreadProcessId(hwnd, Some(&mut pid));
+
+        windows.push(WindowInfo {
+            id: hwnd.0 as usize as u64,
+            title,
+            process: process_name(pid),
+            pid: Some(pid),
+            x: rect.left,
+            y: rect.top,
+            width: (rect.right - rect.left).max(0) as u32,
+            height: (rect.bottom - rect.top).max(0) as u32,
+        });
+        BOOL(1)
+    }
+
+    pub fn list_windows() -> Result<Vec<WindowInfo>, String> {
+        let mut windows: Vec<WindowInfo> = Vec::new();
+        unsafe {
+            EnumWindows(Some(enum_window), LPARAM(&mut windows as *mut Vec<WindowInfo> as isize))
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(windows)
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+mod platform {
+    use super::WindowInfo;
+
+    pub fn list_windows() -> Result<Vec<WindowInfo>, String> {
+        Err("Window capture is not supported on this platform".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(id: u64, x: i32, y: i32, width: u32, height: u32) -> WindowInfo {
+        WindowInfo { id, title: format!("Window {}", id), process: "test".to_string(), pid: None, x, y, width, height }
+    }
+
+    fn bounds(x: i32, y: i32, width: i32, height: i32) -> SelectionBounds {
+        SelectionBounds { x, y, width, height }
+    }
+
+    fn rect(x: u32, y: u32, width: u32, height: u32) -> PixelRect {
+        PixelRect { x, y, width, height }
+    }
+
+    // Second display, right of a 1920 px wide one, at 150 %: 1920x1080 logical, 2880x1620 physical
+    fn display() -> DisplayInfo {
+        DisplayInfo { id: 2, x: 1920, y: 0, width: 1920, height: 1080, scale_factor: 1.5, is_primary: false }
+    }
+
+    #[test]
+    fn windows_are_clipped_to_the_frame() {
+        let windows = [
+            window(1, 1800, -20, 300, 100),  // Overhangs the left and top edges
+            window(2, 4720, 1600, 200, 100), // Overhangs the right and bottom edges
+            window(3, 0, 0, 800, 600),       // On the other display
+        ];
+        let targets = snap_targets(&windows, &display(), 2880, 1620);
+
+        assert_eq!(targets.iter().map(|t| t.id).collect::<Vec<_>>(), [1, 2]);
+        assert_eq!(targets[0].rect, rect(0, 0, 180, 80));
+        assert_eq!(targets[1].rect, rect(2800, 1600, 80, 20));
+    }
+
+    #[test]
+    fn physical_rect_is_kept_exact_at_fractional_scale() {
+        let targets = snap_targets(&[window(1, 2221, 151, 601, 401)], &display(), 2880, 1620);
+
+        // Logical bounds are rounded for the selection window...
+        assert_eq!(targets[0].bounds, bounds(201, 101, 401, 267));
+        // ...scaling them back would give 301,151 602x401 (outwards); the crop uses the window itself
+        assert_eq!(targets[0].rect, rect(301, 151, 601, 401));
+    }
+}
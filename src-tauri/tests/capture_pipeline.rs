@@ -0,0 +1,225 @@
+// Headless capture pipeline: file-backed frame -> selection crop -> timestamp banner -> file,
+// the selection step of the selection window (prepare_selection, behind process_selection),
+// the cursor in headless captures, and the banner QR code read back from the saved file
+
+use app_lib::capture::{self, CaptureSource, DisplayInfo, FileCaptureSource};
+use app_lib::context_capture::{ContextMode, ContextOptions};
+use app_lib::cursor::{CursorOptions, CursorSprite};
+use app_lib::selection::PixelRect;
+use app_lib::{
+    banner_qr, barcode, crop_selection, prepare_selection, write_screenshot, RawScreenshot, SaveData, SelectionBounds,
+    TimestampOptions,
+};
+use chrono::{DateTime, Local};
+use image::{imageops, Rgba, RgbaImage};
+
+// Every pixel differs from its neighbours, so an offset crop cannot match by accident
+fn source_pixel(x: u32, y: u32) -> Rgba<u8> {
+    Rgba([(x % 256) as u8, (y % 256) as u8, ((x * 7 + y * 13) % 256) as u8, 255])
+}
+
+#[test]
+fn file_frame_is_cropped_stamped_and_saved() {
+    let dir = tempfile::tempdir().unwrap();
+    let frame_path = dir.path().join("frame.png");
+    RgbaImage::from_fn(400, 200, source_pixel).save(&frame_path).unwrap();
+
+    // 2x display: the logical selection maps to twice as many physical pixels
+    let source = FileCaptureSource::new(vec![frame_path]).with_scale_factor(2.0);
+    let display = source.displays().unwrap().remove(0);
+    assert_eq!((display.width, display.height), (200, 100));
+    let raw = source.capture_display(&display).unwrap();
+
+    let bounds = SelectionBounds { x: 10, y: 20, width: 50, height: 30 };
+    let cropped = crop_selection(&raw, &bounds).unwrap();
+
+    let options = TimestampOptions {
+        font_size: 14,
+        display_type: "banner-dark".to_string(),
+        position: "bottom".to_string(),
+        ..Default::default()
+    };
+    let data = SaveData {
+        filename: "capture".to_string(),
+        timestamp_options: options,
+        image_format: "png".to_string(),
+        output_folder: None,
+        annotations: Vec::new(),
+        redactions: Vec::new(),
+    };
+    let out_dir = dir.path().join("out");
+    let (path, processed) = write_screenshot(&cropped, raw.scale_factor, None, &out_dir, &data).unwrap();
+    assert_eq!(path, out_dir.join("capture.png"));
+
+    // Selection (100x60 physical) above a 34 px banner (font size + 20)
+    let saved = image::open(&path).unwrap().to_rgba8();
+    assert_eq!(saved.dimensions(), (100, 94));
+    assert_eq!((processed.width, processed.height), (100, 94));
+    assert_eq!(&processed.rgba_pixels, saved.as_raw());
+
+    for y in 0..60 {
+        for x in 0..100 {
+            assert_eq!(*saved.get_pixel(x, y), source_pixel(20 + x, 40 + y), "pixel {},{}", x, y);
+        }
+    }
+
+    // Banner background above the centered text, and at its corners
+    let banner = Rgba([51, 51, 51, 255]);
+    for x in 0..100 {
+        assert_eq!(*saved.get_pixel(x, 60), banner);
+    }
+    assert_eq!(*saved.get_pixel(0, 93), banner);
+    assert_eq!(*saved.get_pixel(99, 93), banner);
+    // The timestamp text is drawn in the banner
+    let text_pixels = (61..93).flat_map(|y| (0..100).map(move |x| (x, y))).filter(|&(x, y)| *saved.get_pixel(x, y) != banner);
+    assert!(text_pixels.count() > 0);
+}
+
+#[test]
+fn selection_is_prepared_with_its_context_and_saved() {
+    let dir = tempfile::tempdir().unwrap();
+    let frame_path = dir.path().join("frame.png");
+    RgbaImage::from_fn(400, 200, source_pixel).save(&frame_path).unwrap();
+
+    let source = FileCaptureSource::new(vec![frame_path]).with_scale_factor(2.0);
+    let display = source.displays().unwrap().remove(0);
+    let raw = source.capture_display(&display).unwrap();
+
+    // File frames carry no cursor: including it leaves the pixels alone
+    let cursor = CursorOptions { include: true, halo: true };
+    let context = ContextOptions { mode: ContextMode::Outline, blur: false };
+    let bounds = SelectionBounds { x: 50, y: 25, width: 60, height: 40 };
+    let (cropped, frame) = prepare_selection(&raw, &bounds, None, cursor, context).unwrap();
+
+    // Selection at 2x: 120x80 physical pixels at 100,50
+    let selection = image::load_from_memory(&cropped).unwrap().to_rgba8();
+    assert_eq!(selection.dimensions(), (120, 80));
+    assert_eq!(*selection.get_pixel(0, 0), source_pixel(100, 50));
+    assert_eq!(*selection.get_pixel(119, 79), source_pixel(219, 129));
+    let frame = frame.expect("outline mode keeps the whole frame");
+    assert_eq!((frame.x, frame.y), (100, 50));
+
+    let data = SaveData {
+        filename: "context".to_string(),
+        timestamp_options: TimestampOptions { enabled: false, ..Default::default() },
+        image_format: "png".to_string(),
+        output_folder: None,
+        annotations: Vec::new(),
+        redactions: Vec::new(),
+    };
+    let (path, _) = write_screenshot(&cropped, raw.scale_factor, Some(&frame), &dir.path().join("out"), &data).unwrap();
+    let saved = image::open(&path).unwrap().to_rgba8();
+
+    // Whole frame, the selection in place inside an 8 px (4 logical) red outline
+    let outline = Rgba([255, 59, 48, 255]);
+    assert_eq!(saved.dimensions(), (400, 200));
+    assert_eq!(*saved.get_pixel(100, 50), source_pixel(100, 50));
+    assert_eq!(*saved.get_pixel(219, 129), source_pixel(219, 129));
+    assert_eq!(*saved.get_pixel(99, 50), outline);
+    assert_eq!(*saved.get_pixel(92, 42), outline);
+    assert_eq!(*saved.get_pixel(227, 137), outline);
+    assert_eq!(*saved.get_pixel(91, 50), source_pixel(91, 50));
+    assert_eq!(*saved.get_pixel(228, 137), source_pixel(228, 137));
+
+    // A snapped window is cropped with its own physical rectangle, not the rounded bounds
+    let snapped = PixelRect { x: 101, y: 51, width: 119, height: 79 };
+    let (cropped, _) = prepare_selection(&raw, &bounds, Some(snapped), cursor, ContextOptions::default()).unwrap();
+    let selection = image::load_from_memory(&cropped).unwrap().to_rgba8();
+    assert_eq!(selection.dimensions(), (119, 79));
+    assert_eq!(*selection.get_pixel(0, 0), source_pixel(101, 51));
+}
+
+#[test]
+fn banner_qr_code_decodes_to_the_capture_metadata() {
+    let dir = tempfile::tempdir().unwrap();
+    let frame_path = dir.path().join("frame.png");
+    RgbaImage::from_fn(400, 200, source_pixel).save(&frame_path).unwrap();
+
+    let source = FileCaptureSource::new(vec![frame_path]);
+    let display = source.displays().unwrap().remove(0);
+    let raw = source.capture_display(&display).unwrap();
+    let bounds = SelectionBounds { x: 20, y: 10, width: 320, height: 120 };
+    let cropped = crop_selection(&raw, &bounds).unwrap();
+
+    let options = TimestampOptions {
+        font_size: 20,
+        qr_code: true,
+        caption: Some("Ticket #4521".to_string()),
+        ..Default::default()
+    };
+    let data = SaveData {
+        filename: "evidence".to_string(),
+        timestamp_options: options,
+        image_format: "png".to_string(),
+        output_folder: None,
+        annotations: Vec::new(),
+        redactions: Vec::new(),
+    };
+    let (path, _) = write_screenshot(&cropped, raw.scale_factor, None, &dir.path().join("out"), &data).unwrap();
+    let saved = image::open(&path).unwrap().to_rgba8();
+
+    let codes = barcode::decode(&saved, 1.0);
+    assert_eq!(codes.len(), 1, "one QR code in the banner");
+
+    // Same payload rebuilt from the saved selection pixels and the encoded time
+    let decoded: serde_json::Value = serde_json::from_str(&codes[0].text).unwrap();
+    let time = DateTime::parse_from_rfc3339(decoded["time"].as_str().unwrap()).unwrap().with_timezone(&Local);
+    assert!((Local::now() - time).num_seconds().abs() < 60);
+    let selection = imageops::crop_imm(&saved, 0, 0, 320, 120).to_image();
+    let expected = banner_qr::payload(&selection, &time, Some("Ticket #4521"));
+    assert_eq!(codes[0].text, serde_json::to_string(&expected).unwrap());
+}
+
+// White frames with a black 4x4 cursor at 10,20, kept apart like the screen source does
+struct CursorSource;
+
+impl CursorSource {
+    fn frame() -> RawScreenshot {
+        RawScreenshot {
+            data: vec![255; 100 * 50 * 4],
+            width: 100,
+            height: 50,
+            scale_factor: 1.0,
+            cursor: Some(CursorSprite {
+                image: RgbaImage::from_pixel(4, 4, Rgba([0, 0, 0, 255])),
+                x: 10,
+                y: 20,
+                hotspot_x: 0,
+                hotspot_y: 0,
+            }),
+        }
+    }
+}
+
+impl CaptureSource for CursorSource {
+    fn displays(&self) -> Result<Vec<DisplayInfo>, String> {
+        Ok(vec![DisplayInfo { id: 0, x: 0, y: 0, width: 100, height: 50, scale_factor: 1.0, is_primary: true }])
+    }
+
+    fn capture_display(&self, _display: &DisplayInfo) -> Result<RawScreenshot, String> {
+        Ok(Self::frame())
+    }
+
+    fn capture_area(&self, _display: &DisplayInfo, _x: i32, _y: i32, _width: u32, _height: u32) -> Result<RawScreenshot, String> {
+        Ok(Self::frame())
+    }
+}
+
+#[test]
+fn headless_captures_draw_the_cursor_when_asked() {
+    let white = Rgba([255, 255, 255, 255]);
+    let black = Rgba([0, 0, 0, 255]);
+
+    let with_cursor = capture::capture_region(&CursorSource, None, None, CursorOptions { include: true, halo: false }).unwrap();
+    assert_eq!(*with_cursor.get_pixel(10, 20), black);
+    assert_eq!(*with_cursor.get_pixel(13, 23), black);
+    assert_eq!(*with_cursor.get_pixel(14, 20), white);
+
+    let without = capture::capture_region(&CursorSource, None, None, CursorOptions::default()).unwrap();
+    assert!(without.pixels().all(|p| *p == white));
+
+    // The raw capture keeps the cursor apart from the pixels
+    let raw = capture::capture_region_raw(&CursorSource, None, None).unwrap();
+    assert!(raw.cursor.is_some());
+    assert!(raw.data.iter().all(|&v| v == 255));
+}